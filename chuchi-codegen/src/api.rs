@@ -227,6 +227,10 @@ pub(crate) fn expand(args: ApiArgs, item: ItemFn) -> Result<TokenStream> {
 
 			#path_fn
 
+			fn kind(&self) -> #chuchi::routes::RouteKind {
+				#chuchi::routes::RouteKind::Api
+			}
+
 			#call_fn
 		}
 	))
@@ -1,12 +1,17 @@
 use syn::parse::{Parse, ParseStream, Result};
-use syn::LitStr;
+use syn::punctuated::Punctuated;
+use syn::{bracketed, Ident, LitBool, LitStr, Token, Type};
 
 #[cfg(feature = "api")]
 pub(crate) use api::*;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub(crate) struct Args {
 	pub uri: String,
+	pub skip_middleware: Vec<Type>,
+	pub head: bool,
+	pub name: Option<String>,
+	pub host: Option<String>,
 }
 
 impl Parse for Args {
@@ -14,7 +19,45 @@ impl Parse for Args {
 		// parse a string
 		let uri: LitStr = input.parse()?;
 
-		Ok(Self { uri: uri.value() })
+		let mut me = Self {
+			uri: uri.value(),
+			skip_middleware: vec![],
+			head: true,
+			name: None,
+			host: None,
+		};
+
+		// options in the form of `, key = value`
+		while !input.is_empty() {
+			input.parse::<Token![,]>()?;
+			if input.is_empty() {
+				break;
+			}
+
+			let ident = input.parse::<Ident>()?;
+			let _eq = input.parse::<Token![=]>()?;
+
+			if ident == "skip_middleware" {
+				let content;
+				bracketed!(content in input);
+				let types =
+					Punctuated::<Type, Token![,]>::parse_terminated(&content)?;
+				me.skip_middleware.extend(types);
+			} else if ident == "head" {
+				me.head = input.parse::<LitBool>()?.value;
+			} else if ident == "name" {
+				me.name = Some(input.parse::<LitStr>()?.value());
+			} else if ident == "host" {
+				me.host = Some(input.parse::<LitStr>()?.value());
+			} else {
+				return Err(syn::Error::new(
+					ident.span(),
+					"expected `skip_middleware`, `head`, `name` or `host`",
+				));
+			}
+		}
+
+		Ok(me)
 	}
 }
 
@@ -22,8 +65,6 @@ impl Parse for Args {
 mod api {
 	use super::*;
 
-	use syn::{Ident, LitBool, Token, Type};
-
 	#[derive(Clone)]
 	pub(crate) struct ApiArgs {
 		pub ty: Type,
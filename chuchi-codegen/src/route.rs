@@ -1,4 +1,6 @@
-use crate::util::{chuchi_crate, validate_inputs, validate_signature};
+use crate::util::{
+	chuchi_crate, generate_url_fn, validate_inputs, validate_signature,
+};
 use crate::Args;
 use crate::{Method, TransformOutput};
 
@@ -75,6 +77,36 @@ pub(crate) fn expand(
 		)
 	};
 
+	let name_and_host_fns = generate_name_and_host_fns(&args);
+	let url_fn = generate_url_fn(&args, &item)?;
+
+	let skip_middleware_fn = if args.skip_middleware.is_empty() {
+		quote!()
+	} else {
+		let types = &args.skip_middleware;
+
+		quote!(
+			fn skip_middleware(&self, middleware: std::any::TypeId) -> bool {
+				[#(std::any::TypeId::of::<#types>()),*].contains(&middleware)
+			}
+		)
+	};
+
+	let head_fallback_fn = if args.head {
+		quote!()
+	} else if matches!(method, Method::Get) {
+		quote!(
+			fn head_fallback(&self) -> bool {
+				false
+			}
+		)
+	} else {
+		return Err(syn::Error::new(
+			item.sig.ident.span(),
+			"`head = false` can only be used on get routes",
+		));
+	};
+
 	let route_fn = {
 		let asyncness = &item.sig.asyncness;
 		let inputs = &item.sig.inputs;
@@ -103,8 +135,8 @@ pub(crate) fn expand(
 				#(#attrs)*
 				{
 					let prepare = #chuchi::extractor::Prepare::new(
-						#name, req.header(), params, &mut state, resources
-					);
+						#name, &req.header, params, &mut state, resources
+					).with_body(&mut req.body);
 
 					let res = <#ty as #extractor_type>::prepare(
 						prepare
@@ -190,16 +222,44 @@ pub(crate) fn expand(
 	Ok(quote!(
 		#struct_gen
 
+		#url_fn
+
 		impl #chuchi::routes::Route for #struct_name {
 			#valid_data_fn
 
 			#path_fn
 
+			#name_and_host_fns
+
+			#skip_middleware_fn
+
+			#head_fallback_fn
+
 			#call_fn
 		}
 	))
 }
 
+pub(crate) fn generate_name_and_host_fns(args: &Args) -> TokenStream {
+	let name_fn = args.name.as_ref().map(|name| {
+		quote!(
+			fn name(&self) -> Option<&str> {
+				Some(#name)
+			}
+		)
+	});
+
+	let host_fn = args.host.as_ref().map(|host| {
+		quote!(
+			fn host(&self) -> Option<&str> {
+				Some(#host)
+			}
+		)
+	});
+
+	quote!(#name_fn #host_fn)
+}
+
 pub(crate) fn generate_struct(item: &ItemFn) -> TokenStream {
 	let struct_name = &item.sig.ident;
 	let attrs = &item.attrs;
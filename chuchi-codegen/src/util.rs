@@ -1,7 +1,9 @@
+use crate::Args;
+
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 use syn::{
-	punctuated, Attribute, Error, FnArg, Pat, Result, Signature, Type,
+	punctuated, Attribute, Error, FnArg, ItemFn, Pat, Result, Signature, Type,
 	TypeReference,
 };
 
@@ -31,6 +33,77 @@ pub(crate) fn validate_signature(sig: &Signature) -> Result<()> {
 	Ok(())
 }
 
+/// Returns the names of all params in the uri, `{*rest}` and `{*?rest}`
+/// return `rest`.
+fn uri_params(uri: &str) -> Vec<&str> {
+	let mut params = vec![];
+	let mut rest = uri;
+
+	while let Some(start) = rest.find('{') {
+		rest = &rest[start + 1..];
+
+		// escaped brace
+		if let Some(r) = rest.strip_prefix('{') {
+			rest = r;
+			continue;
+		}
+
+		let Some(end) = rest.find('}') else {
+			break;
+		};
+
+		let param = &rest[..end];
+		let param = param.strip_prefix("*?").unwrap_or(param);
+		params.push(param.strip_prefix('*').unwrap_or(param));
+
+		rest = &rest[end + 1..];
+	}
+
+	params
+}
+
+/// Generates an inherent `url` method which fills the params of the uri.
+pub(crate) fn generate_url_fn(
+	args: &Args,
+	item: &ItemFn,
+) -> Result<TokenStream> {
+	let chuchi = chuchi_crate()?;
+	let struct_name = &item.sig.ident;
+	let vis = &item.vis;
+	let uri = &args.uri;
+
+	let names = uri_params(uri);
+	let idents = names
+		.iter()
+		.map(|name| {
+			syn::parse_str::<Ident>(name).map_err(|_| {
+				Error::new(
+					struct_name.span(),
+					format!("the param `{name}` is not a valid identifier"),
+				)
+			})
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	Ok(quote!(
+		impl #struct_name {
+			/// Returns the path of this route with the params filled in and
+			/// percent-encoded.
+			///
+			/// The prefix of a scope the route is mounted in is not included.
+			#[allow(dead_code)]
+			#vis fn url(
+				&self,
+				#(#idents: impl std::fmt::Display),*
+			) -> std::string::String {
+				#chuchi::routes::fill_path(#uri, &[
+					#((#names, #idents.to_string().as_str())),*
+				]).expect("all params are provided")
+			}
+		}
+	))
+}
+
 #[allow(dead_code)]
 pub(crate) fn ref_type(ty: &Type) -> Option<&TypeReference> {
 	match ty {
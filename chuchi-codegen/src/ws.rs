@@ -1,5 +1,7 @@
-use crate::route::generate_struct;
-use crate::util::{chuchi_crate, validate_inputs, validate_signature};
+use crate::route::{generate_name_and_host_fns, generate_struct};
+use crate::util::{
+	chuchi_crate, generate_url_fn, validate_inputs, validate_signature,
+};
 use crate::Args;
 
 use proc_macro2::{Literal, TokenStream};
@@ -11,6 +13,20 @@ pub(crate) fn expand(args: Args, item: ItemFn) -> Result<TokenStream> {
 
 	validate_signature(&item.sig)?;
 
+	if let Some(ty) = args.skip_middleware.first() {
+		return Err(syn::Error::new_spanned(
+			ty,
+			"websockets are not called through middlewares",
+		));
+	}
+
+	if !args.head {
+		return Err(syn::Error::new(
+			item.sig.ident.span(),
+			"websockets are never called for head requests",
+		));
+	}
+
 	// Box<Type>
 	let inputs = validate_inputs(item.sig.inputs.iter())?;
 
@@ -65,6 +81,9 @@ pub(crate) fn expand(args: Args, item: ItemFn) -> Result<TokenStream> {
 		)
 	};
 
+	let name_and_host_fns = generate_name_and_host_fns(&args);
+	let url_fn = generate_url_fn(&args, &item)?;
+
 	let handler_fn = {
 		let asyncness = &item.sig.asyncness;
 		let inputs = &item.sig.inputs;
@@ -199,11 +218,19 @@ pub(crate) fn expand(args: Args, item: ItemFn) -> Result<TokenStream> {
 	Ok(quote!(
 		#struct_gen
 
+		#url_fn
+
 		impl #chuchi::routes::RawRoute for #struct_name {
 			#valid_data_fn
 
 			#path_fn
 
+			#name_and_host_fns
+
+			fn kind(&self) -> #chuchi::routes::RouteKind {
+				#chuchi::routes::RouteKind::Ws
+			}
+
 			#call_fn
 		}
 	))
@@ -139,9 +139,11 @@ impl<R: AsyncRead> AsyncRead for ConstrainedAsyncReader<R> {
 				match size_limit.checked_sub(read) {
 					Some(ns) => *size_limit = ns,
 					None => {
+						// an error should not report any read bytes
+						buf.set_filled(prev_filled);
 						return Poll::Ready(Err(size_limit_reached(
 							"async reader to big",
-						)))
+						)));
 					}
 				}
 			}
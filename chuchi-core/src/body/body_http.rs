@@ -1,5 +1,6 @@
 use super::{BodyAsyncBytesStreamer, Constraints};
 
+use std::error::Error as StdError;
 use std::io;
 use std::pin::Pin;
 use std::task::{Context, Poll};
@@ -12,6 +13,8 @@ use pin_project_lite::pin_project;
 
 use bytes::Bytes;
 
+use sync_wrapper::SyncWrapper;
+
 pin_project! {
 	pub struct BodyHttp {
 		#[pin]
@@ -84,3 +87,53 @@ impl Stream for HyperBodyAsAsyncBytesStream {
 		}
 	}
 }
+
+/// Converts any `http_body::Body` into a bytes stream.
+///
+/// The body is wrapped in a `SyncWrapper` since it is only ever accessed
+/// mutably, this allows to use bodies which are not `Sync`.
+pub(super) struct HttpBodyAsAsyncBytesStream<B> {
+	inner: SyncWrapper<Pin<Box<B>>>,
+}
+
+impl<B> HttpBodyAsAsyncBytesStream<B> {
+	pub fn new(inner: B) -> Self {
+		Self {
+			inner: SyncWrapper::new(Box::pin(inner)),
+		}
+	}
+}
+
+impl<B> Stream for HttpBodyAsAsyncBytesStream<B>
+where
+	B: HyperBody<Data = Bytes>,
+	B::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+	type Item = io::Result<Bytes>;
+
+	fn poll_next(
+		self: Pin<&mut Self>,
+		cx: &mut Context,
+	) -> Poll<Option<io::Result<Bytes>>> {
+		let inner = self.get_mut().inner.get_mut();
+		// loop to skip trailers
+		loop {
+			let r = match inner.as_mut().poll_frame(cx) {
+				Poll::Ready(Some(Ok(frame))) => {
+					let Ok(data) = frame.into_data() else {
+						continue;
+					};
+
+					Poll::Ready(Some(Ok(data)))
+				}
+				Poll::Ready(Some(Err(e))) => {
+					Poll::Ready(Some(Err(io::Error::other(e.into()))))
+				}
+				Poll::Ready(None) => Poll::Ready(None),
+				Poll::Pending => Poll::Pending,
+			};
+
+			break r;
+		}
+	}
+}
@@ -12,8 +12,9 @@ pub use async_bytes_streamer::BodyAsyncBytesStreamer;
 
 mod body_http;
 pub use body_http::BodyHttp;
-use body_http::HyperBodyAsAsyncBytesStream;
+use body_http::{HttpBodyAsAsyncBytesStream, HyperBodyAsAsyncBytesStream};
 
+use std::error::Error as StdError;
 use std::io::Read as SyncRead;
 use std::pin::Pin;
 use std::time::Duration;
@@ -25,7 +26,7 @@ use tokio::task;
 use futures_core::Stream as AsyncStream;
 
 use bytes::Bytes;
-use hyper::body::Incoming;
+use hyper::body::{Body as HttpBody, Incoming};
 
 type PinnedAsyncRead = Pin<Box<dyn AsyncRead + Send + Sync>>;
 type BoxedSyncRead = Box<dyn SyncRead + Send + Sync>;
@@ -67,6 +68,40 @@ struct Constraints {
 	size: Option<usize>,
 }
 
+/// The encoding of a compressed body.
+#[cfg(feature = "decompression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "decompression")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ContentEncoding {
+	Gzip,
+	Deflate,
+	Brotli,
+}
+
+#[cfg(feature = "decompression")]
+impl ContentEncoding {
+	/// Parses the value of a `Content-Encoding` header.
+	///
+	/// Returns `None` if the encoding is not supported or multiple encodings
+	/// were applied.
+	pub fn from_header(value: &str) -> Option<Self> {
+		let value = value.trim();
+
+		if value.eq_ignore_ascii_case("gzip")
+			|| value.eq_ignore_ascii_case("x-gzip")
+		{
+			Some(Self::Gzip)
+		} else if value.eq_ignore_ascii_case("deflate") {
+			Some(Self::Deflate)
+		} else if value.eq_ignore_ascii_case("br") {
+			Some(Self::Brotli)
+		} else {
+			None
+		}
+	}
+}
+
 #[derive(Debug, Default)]
 pub struct Body {
 	inner: Inner,
@@ -111,6 +146,18 @@ impl Body {
 		Self::new_inner(Inner::Hyper(body))
 	}
 
+	/// Creates a new Body from any type that implements
+	/// `http_body::Body`.
+	///
+	/// Trailers are ignored.
+	pub fn from_http_body<B>(body: B) -> Self
+	where
+		B: HttpBody<Data = Bytes> + Send + 'static,
+		B::Error: Into<Box<dyn StdError + Send + Sync>>,
+	{
+		Self::from_async_bytes_streamer(HttpBodyAsAsyncBytesStream::new(body))
+	}
+
 	/// Creates a new Body from a `Read` implementation.
 	pub fn from_sync_reader<R>(reader: R) -> Self
 	where
@@ -173,6 +220,34 @@ impl Body {
 		self.constraints.timeout = timeout;
 	}
 
+	/// Decompresses the body while it gets read.
+	///
+	/// The size limit applies to the decompressed data, which protects
+	/// against zip bombs.
+	#[cfg(feature = "decompression")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "decompression")))]
+	pub fn decompress(&mut self, encoding: ContentEncoding) {
+		use async_compression::tokio::bufread::{
+			BrotliDecoder, GzipDecoder, ZlibDecoder,
+		};
+		use tokio::io::BufReader;
+
+		let inner = mem::take(&mut self.inner);
+		if matches!(inner, Inner::Empty) {
+			return;
+		}
+
+		// the constraints are applied to the decompressed data
+		let reader =
+			BufReader::new(BodyAsyncReader::new(inner, Constraints::default()));
+
+		self.inner = Inner::AsyncReader(match encoding {
+			ContentEncoding::Gzip => Box::pin(GzipDecoder::new(reader)),
+			ContentEncoding::Deflate => Box::pin(ZlibDecoder::new(reader)),
+			ContentEncoding::Brotli => Box::pin(BrotliDecoder::new(reader)),
+		});
+	}
+
 	/// Takes the body and replaces it with an empty one.
 	pub fn take(&mut self) -> Self {
 		mem::take(self)
@@ -313,6 +388,65 @@ mod tests {
 		is_send::<Body>();
 		is_sync::<Body>();
 	}
+
+	#[tokio::test]
+	async fn from_http_body() {
+		let body = Body::from_http_body(String::from("Hello, World!"));
+		assert_eq!(body.into_string().await.unwrap(), "Hello, World!");
+	}
+}
+
+#[cfg(all(test, feature = "decompression"))]
+mod decompression_tests {
+	use super::*;
+	use async_compression::tokio::bufread::GzipEncoder;
+	use tokio::io::AsyncReadExt;
+
+	async fn gzip(data: &[u8]) -> Vec<u8> {
+		let mut encoded = vec![];
+		GzipEncoder::new(data)
+			.read_to_end(&mut encoded)
+			.await
+			.unwrap();
+		encoded
+	}
+
+	#[test]
+	fn content_encoding_from_header() {
+		let enc = ContentEncoding::from_header;
+		assert_eq!(enc("gzip"), Some(ContentEncoding::Gzip));
+		assert_eq!(enc(" X-Gzip "), Some(ContentEncoding::Gzip));
+		assert_eq!(enc("deflate"), Some(ContentEncoding::Deflate));
+		assert_eq!(enc("br"), Some(ContentEncoding::Brotli));
+		assert_eq!(enc("gzip, br"), None);
+		assert_eq!(enc("zstd"), None);
+	}
+
+	#[tokio::test]
+	async fn decompress_gzip() {
+		let mut body = Body::from(gzip(b"Hello, World!").await);
+		body.decompress(ContentEncoding::Gzip);
+		assert_eq!(body.len(), None);
+		assert_eq!(body.into_string().await.unwrap(), "Hello, World!");
+	}
+
+	#[tokio::test]
+	async fn decompress_size_limit() {
+		// compresses to a few bytes
+		let data = vec![0u8; 1024 * 1024];
+		let mut body = Body::from(gzip(&data).await);
+		body.set_size_limit(Some(1024));
+		body.decompress(ContentEncoding::Gzip);
+
+		assert!(body.into_bytes().await.is_err());
+	}
+
+	#[tokio::test]
+	async fn decompress_empty() {
+		let mut body = Body::new();
+		body.decompress(ContentEncoding::Brotli);
+		assert!(body.is_empty());
+	}
 }
 
 #[cfg(all(test, feature = "json"))]
@@ -29,6 +29,22 @@ impl Mime {
 	pub fn as_str_with_maybe_charset(&self) -> &'static str {
 		self.0.as_str_with_maybe_charset()
 	}
+
+	/// Returns `false` if the format is already compressed, for example
+	/// most images, videos, audio, fonts and archives.
+	pub fn is_compressible(&self) -> bool {
+		use MimeValue::*;
+
+		match self.0 {
+			JPG | PNG | GIF | WBP => false,
+			WOFF | WOOF2 => false,
+			AVI | OGV | WEBM | MP4 => false,
+			AAC | MP3 | OGA | WEBA => false,
+			// zip based formats
+			RAR | ZIP | _7ZIP | JAR | DOCX => false,
+			_ => true,
+		}
+	}
 }
 
 impl fmt::Display for Mime {
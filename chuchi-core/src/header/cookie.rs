@@ -0,0 +1,503 @@
+//! Cookies sent by the client and the `Set-Cookie` header.
+//!
+//! Values get percent encoded when they contain characters which are not
+//! allowed in a cookie and are decoded again when parsing.
+
+use super::values::HeaderValues;
+use super::{HeaderValue, COOKIE};
+
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use percent_encoding::{AsciiSet, CONTROLS};
+
+/// Characters which need to be encoded in a cookie value.
+const VALUE: &AsciiSet = &CONTROLS
+	.add(b' ')
+	.add(b'"')
+	.add(b',')
+	.add(b';')
+	.add(b'\\')
+	.add(b'%');
+
+/// The cookies the client sent with a request.
+///
+/// If a cookie name appears multiple times the first one is returned by
+/// `get`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cookies {
+	cookies: Vec<(String, String)>,
+}
+
+impl Cookies {
+	/// Creates an empty `Cookies`.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Parses the value of a `Cookie` header.
+	///
+	/// Invalid pairs are skipped.
+	pub fn parse(s: &str) -> Self {
+		let mut cookies = Self::new();
+		cookies.extend_from_str(s);
+		cookies
+	}
+
+	/// Parses every `Cookie` header.
+	pub fn from_values(values: &HeaderValues) -> Self {
+		let mut cookies = Self::new();
+		for value in values.get_all(COOKIE) {
+			if let Ok(s) = value.to_str() {
+				cookies.extend_from_str(s);
+			}
+		}
+
+		cookies
+	}
+
+	fn extend_from_str(&mut self, s: &str) {
+		let pairs = s.split(';').filter_map(|pair| {
+			let (name, value) = pair.trim().split_once('=')?;
+			let name = name.trim();
+			if name.is_empty() {
+				return None;
+			}
+
+			let value = value.trim();
+			let value = value
+				.strip_prefix('"')
+				.and_then(|v| v.strip_suffix('"'))
+				.unwrap_or(value);
+			let value = percent_encoding::percent_decode_str(value)
+				.decode_utf8_lossy()
+				.into_owned();
+
+			Some((name.to_string(), value))
+		});
+
+		self.cookies.extend(pairs);
+	}
+
+	/// Returns the value of the cookie if it exists.
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.cookies
+			.iter()
+			.find(|(n, _)| n == name)
+			.map(|(_, v)| v.as_str())
+	}
+
+	/// Returns every value of the cookie.
+	pub fn get_all<'a>(
+		&'a self,
+		name: &'a str,
+	) -> impl Iterator<Item = &'a str> + 'a {
+		self.cookies
+			.iter()
+			.filter(move |(n, _)| n == name)
+			.map(|(_, v)| v.as_str())
+	}
+
+	/// Returns `true` if the cookie exists.
+	pub fn contains(&self, name: &str) -> bool {
+		self.get(name).is_some()
+	}
+
+	/// Returns every cookie as a name value pair.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+		self.cookies.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+	}
+
+	pub fn len(&self) -> usize {
+		self.cookies.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.cookies.is_empty()
+	}
+}
+
+/// The `SameSite` attribute of a cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SameSite {
+	Strict,
+	Lax,
+	/// Browsers only accept this if the cookie is also `Secure`.
+	None,
+}
+
+impl SameSite {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Strict => "Strict",
+			Self::Lax => "Lax",
+			Self::None => "None",
+		}
+	}
+}
+
+impl fmt::Display for SameSite {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// A cookie which can be sent to the client with a `Set-Cookie` header.
+///
+/// ## Example
+/// ```
+/// use chuchi_core::header::{Cookie, SameSite};
+/// use std::time::Duration;
+///
+/// let cookie = Cookie::builder("session", "abc")
+/// 	.path("/")
+/// 	.max_age(Duration::from_secs(60))
+/// 	.http_only()
+/// 	.same_site(SameSite::Lax)
+/// 	.build();
+///
+/// assert_eq!(
+/// 	cookie.to_string(),
+/// 	"session=abc; Path=/; Max-Age=60; HttpOnly; SameSite=Lax"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+	name: String,
+	value: String,
+	path: Option<String>,
+	domain: Option<String>,
+	max_age: Option<Duration>,
+	expires: Option<SystemTime>,
+	secure: bool,
+	http_only: bool,
+	same_site: Option<SameSite>,
+	partitioned: bool,
+}
+
+impl Cookie {
+	/// Creates a new `Cookie` without any attributes.
+	///
+	/// ## Panics
+	/// If the name is not a valid token.
+	#[track_caller]
+	pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+		let name = name.into();
+		assert!(is_valid_name(&name), "invalid cookie name {name:?}");
+
+		Self {
+			name,
+			value: value.into(),
+			path: None,
+			domain: None,
+			max_age: None,
+			expires: None,
+			secure: false,
+			http_only: false,
+			same_site: None,
+			partitioned: false,
+		}
+	}
+
+	/// Creates a new `CookieBuilder`.
+	///
+	/// ## Panics
+	/// If the name is not a valid token.
+	#[track_caller]
+	pub fn builder(
+		name: impl Into<String>,
+		value: impl Into<String>,
+	) -> CookieBuilder {
+		CookieBuilder::new(name, value)
+	}
+
+	/// Creates a `CookieBuilder` for a cookie which tells the client to
+	/// remove the cookie with the given name.
+	///
+	/// The path and domain need to match the ones of the cookie which
+	/// should be removed.
+	#[track_caller]
+	pub fn removal(name: impl Into<String>) -> CookieBuilder {
+		CookieBuilder::new(name, "")
+			.max_age(Duration::ZERO)
+			.expires(SystemTime::UNIX_EPOCH)
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn value(&self) -> &str {
+		&self.value
+	}
+
+	/// Replaces the value, keeping every attribute.
+	pub fn set_value(&mut self, value: impl Into<String>) {
+		self.value = value.into();
+	}
+
+	pub fn path(&self) -> Option<&str> {
+		self.path.as_deref()
+	}
+
+	pub fn domain(&self) -> Option<&str> {
+		self.domain.as_deref()
+	}
+
+	pub fn max_age(&self) -> Option<Duration> {
+		self.max_age
+	}
+
+	pub fn expires(&self) -> Option<SystemTime> {
+		self.expires
+	}
+
+	pub fn secure(&self) -> bool {
+		self.secure
+	}
+
+	pub fn http_only(&self) -> bool {
+		self.http_only
+	}
+
+	pub fn same_site(&self) -> Option<SameSite> {
+		self.same_site
+	}
+
+	pub fn partitioned(&self) -> bool {
+		self.partitioned
+	}
+
+	/// Returns the cookie as a value for the `Set-Cookie` header.
+	pub fn to_header_value(&self) -> HeaderValue {
+		// the value is encoded and every attribute got validated
+		HeaderValue::try_from(self.to_string())
+			.expect("cookie should be a valid header value")
+	}
+}
+
+impl fmt::Display for Cookie {
+	/// Formats the cookie as it is sent in the `Set-Cookie` header.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"{}={}",
+			self.name,
+			percent_encoding::utf8_percent_encode(&self.value, VALUE)
+		)?;
+
+		if let Some(path) = &self.path {
+			write!(f, "; Path={path}")?;
+		}
+
+		if let Some(domain) = &self.domain {
+			write!(f, "; Domain={domain}")?;
+		}
+
+		if let Some(max_age) = self.max_age {
+			write!(f, "; Max-Age={}", max_age.as_secs())?;
+		}
+
+		if let Some(expires) = self.expires {
+			write!(f, "; Expires={}", httpdate::fmt_http_date(expires))?;
+		}
+
+		if self.secure {
+			f.write_str("; Secure")?;
+		}
+
+		if self.http_only {
+			f.write_str("; HttpOnly")?;
+		}
+
+		if let Some(same_site) = self.same_site {
+			write!(f, "; SameSite={same_site}")?;
+		}
+
+		if self.partitioned {
+			f.write_str("; Partitioned")?;
+		}
+
+		Ok(())
+	}
+}
+
+/// A builder to create a `Cookie`.
+#[derive(Debug, Clone)]
+pub struct CookieBuilder {
+	cookie: Cookie,
+}
+
+impl CookieBuilder {
+	/// Creates a new `CookieBuilder`.
+	///
+	/// ## Panics
+	/// If the name is not a valid token.
+	#[track_caller]
+	pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+		Self {
+			cookie: Cookie::new(name, value),
+		}
+	}
+
+	/// Sets the `Path` attribute.
+	///
+	/// ## Panics
+	/// If the path contains a `;` or control characters.
+	#[track_caller]
+	pub fn path(mut self, path: impl Into<String>) -> Self {
+		let path = path.into();
+		assert!(is_valid_attribute(&path), "invalid cookie path {path:?}");
+		self.cookie.path = Some(path);
+		self
+	}
+
+	/// Sets the `Domain` attribute.
+	///
+	/// ## Panics
+	/// If the domain contains a `;` or control characters.
+	#[track_caller]
+	pub fn domain(mut self, domain: impl Into<String>) -> Self {
+		let domain = domain.into();
+		assert!(
+			is_valid_attribute(&domain),
+			"invalid cookie domain {domain:?}"
+		);
+		self.cookie.domain = Some(domain);
+		self
+	}
+
+	/// Sets the `Max-Age` attribute, only whole seconds are used.
+	pub fn max_age(mut self, max_age: Duration) -> Self {
+		self.cookie.max_age = Some(max_age);
+		self
+	}
+
+	/// Sets the `Expires` attribute.
+	///
+	/// If `Max-Age` is also set, it takes precedence.
+	pub fn expires(mut self, expires: SystemTime) -> Self {
+		self.cookie.expires = Some(expires);
+		self
+	}
+
+	/// Sets the `Secure` attribute.
+	pub fn secure(mut self) -> Self {
+		self.cookie.secure = true;
+		self
+	}
+
+	/// Sets the `HttpOnly` attribute.
+	pub fn http_only(mut self) -> Self {
+		self.cookie.http_only = true;
+		self
+	}
+
+	/// Sets the `SameSite` attribute.
+	pub fn same_site(mut self, same_site: SameSite) -> Self {
+		self.cookie.same_site = Some(same_site);
+		self
+	}
+
+	/// Sets the `Partitioned` attribute.
+	///
+	/// Browsers only accept this if the cookie is also `Secure`.
+	pub fn partitioned(mut self) -> Self {
+		self.cookie.partitioned = true;
+		self
+	}
+
+	/// Builds the `Cookie`.
+	pub fn build(self) -> Cookie {
+		self.cookie
+	}
+}
+
+impl From<CookieBuilder> for Cookie {
+	fn from(builder: CookieBuilder) -> Self {
+		builder.build()
+	}
+}
+
+/// Returns `true` if the name is a valid token.
+fn is_valid_name(name: &str) -> bool {
+	!name.is_empty()
+		&& name.bytes().all(|b| {
+			b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&b)
+		})
+}
+
+fn is_valid_attribute(s: &str) -> bool {
+	s.bytes()
+		.all(|b| b.is_ascii() && b != b';' && !b.is_ascii_control())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_cookies() {
+		let cookies = Cookies::parse(
+			"a=1; b=\"two\";c=%F0%9F%9A%80 ; invalid; =empty; a=3",
+		);
+
+		assert_eq!(cookies.get("a"), Some("1"));
+		assert_eq!(cookies.get_all("a").collect::<Vec<_>>(), ["1", "3"]);
+		assert_eq!(cookies.get("b"), Some("two"));
+		assert_eq!(cookies.get("c"), Some("🚀"));
+		assert!(!cookies.contains("invalid"));
+		assert_eq!(cookies.len(), 4);
+	}
+
+	#[test]
+	fn from_multiple_headers() {
+		let mut values = HeaderValues::new();
+		values.append(COOKIE, "a=1");
+		values.append(COOKIE, "b=2");
+
+		let cookies = Cookies::from_values(&values);
+		assert_eq!(
+			cookies.iter().collect::<Vec<_>>(),
+			[("a", "1"), ("b", "2")]
+		);
+	}
+
+	#[test]
+	fn set_cookie() {
+		let cookie = Cookie::builder("id", "a b;c")
+			.path("/")
+			.domain("example.com")
+			.expires(SystemTime::UNIX_EPOCH + Duration::from_secs(784111777))
+			.secure()
+			.http_only()
+			.same_site(SameSite::None)
+			.partitioned()
+			.build();
+
+		assert_eq!(
+			cookie.to_header_value(),
+			"id=a%20b%3Bc; Path=/; Domain=example.com; \
+			Expires=Sun, 06 Nov 1994 08:49:37 GMT; Secure; HttpOnly; \
+			SameSite=None; Partitioned"
+		);
+
+		let cookies = Cookies::parse(&format!("id={}", "a%20b%3Bc"));
+		assert_eq!(cookies.get("id"), Some("a b;c"));
+	}
+
+	#[test]
+	fn removal() {
+		let cookie = Cookie::removal("id").path("/").build();
+		assert_eq!(
+			cookie.to_string(),
+			"id=; Path=/; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT"
+		);
+	}
+
+	#[test]
+	#[should_panic]
+	fn invalid_name() {
+		Cookie::new("a;b", "c");
+	}
+}
@@ -11,6 +11,9 @@ pub use contenttype::{ContentType, Mime};
 pub mod values;
 pub use values::{HeaderValue, HeaderValues};
 
+pub mod cookie;
+pub use cookie::{Cookie, CookieBuilder, Cookies, SameSite};
+
 pub use constants::*;
 
 /// RequestHeader received from a client.
@@ -57,6 +60,11 @@ impl RequestHeader {
 	{
 		self.values.get_str(key)
 	}
+
+	/// Parses the cookies sent with the request.
+	pub fn cookies(&self) -> Cookies {
+		Cookies::from_values(&self.values)
+	}
 }
 
 /// ResponseHeader created from a server.
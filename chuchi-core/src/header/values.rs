@@ -11,8 +11,9 @@ pub use serde_json::Error as JsonError;
 /// Contains all http header values.
 ///
 /// This is really similar to `http::header::HeaderMap` except
-/// that is uses IntoHeaderValue for inserting. And it does not allow
-/// multiples values for a given key.
+/// that is uses IntoHeaderValue for inserting. `insert` replaces
+/// existing values, use `append` to store multiple values for a given key,
+/// for example `Set-Cookie`.
 #[derive(Debug, Clone)]
 pub struct HeaderValues(http::HeaderMap<HeaderValue>);
 
@@ -61,6 +62,22 @@ impl HeaderValues {
 		Ok(self.0.insert(key, val.try_into()?))
 	}
 
+	/// Appends a value to the key, keeping the existing values.
+	///
+	/// Returns `false` if the key did not exist before.
+	///
+	/// ## Panics
+	/// If the value is not a valid HeaderValue.
+	pub fn append<K, V>(&mut self, key: K, val: V) -> bool
+	where
+		K: IntoHeaderName,
+		V: TryInto<HeaderValue>,
+		V::Error: fmt::Debug,
+	{
+		let val = val.try_into().expect("invalid HeaderValue");
+		self.0.append(key, val)
+	}
+
 	/// Insert a new key and value into the header. Percent encoding
 	/// the value if necessary.
 	pub fn encode_value<K, V>(&mut self, key: K, val: V) -> Option<HeaderValue>
@@ -99,6 +116,14 @@ impl HeaderValues {
 		self.0.get(key)
 	}
 
+	/// Returns every value of the key.
+	pub fn get_all<K>(&self, key: K) -> impl Iterator<Item = &HeaderValue>
+	where
+		K: AsHeaderName,
+	{
+		self.0.get_all(key).into_iter()
+	}
+
 	/// Returns the value mutably if it exists.
 	pub fn get_mut<K>(&mut self, key: K) -> Option<&mut HeaderValue>
 	where
@@ -107,6 +132,14 @@ impl HeaderValues {
 		self.0.get_mut(key)
 	}
 
+	/// Removes every value of the key, returning the first one.
+	pub fn remove<K>(&mut self, key: K) -> Option<HeaderValue>
+	where
+		K: AsHeaderName,
+	{
+		self.0.remove(key)
+	}
+
 	/// Returns the value as a string if it exists and is valid.
 	pub fn get_str<K>(&self, key: K) -> Option<&str>
 	where
@@ -1,8 +1,8 @@
 use super::Response;
 use crate::body::Body;
 use crate::header::{
-	values::IntoHeaderName, ContentType, HeaderValue, HeaderValues,
-	ResponseHeader, StatusCode, CONTENT_LENGTH,
+	values::IntoHeaderName, ContentType, Cookie, HeaderValue, HeaderValues,
+	ResponseHeader, StatusCode, CONTENT_LENGTH, SET_COOKIE,
 };
 
 use std::fmt;
@@ -56,6 +56,14 @@ impl ResponseBuilder {
 		self
 	}
 
+	/// Adds a `Set-Cookie` header, keeping the previously set cookies.
+	pub fn cookie(mut self, cookie: impl Into<Cookie>) -> Self {
+		let cookie = cookie.into();
+		self.values_mut()
+			.append(SET_COOKIE, cookie.to_header_value());
+		self
+	}
+
 	/// Returns `HeaderValues` mutably.
 	pub fn values_mut(&mut self) -> &mut HeaderValues {
 		&mut self.header.values
@@ -1,46 +1,17 @@
-use chuchi::header::{
-	Method, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
-	ACCESS_CONTROL_ALLOW_ORIGIN, X_XSS_PROTECTION,
-};
-use chuchi::header::{RequestHeader, ResponseHeader, StatusCode};
-use chuchi::resources::Resources;
-use chuchi::routes::Catcher;
-use chuchi::util::PinnedFuture;
-use chuchi::{get, Request, Response};
+use chuchi::cors::Cors;
+use chuchi::header::Method;
+use chuchi::{get, post};
+
+use std::time::Duration;
 
 #[get("/")]
 fn hello_world() -> &'static str {
 	"Hello, World!"
 }
 
-struct CorsHeaders;
-
-impl Catcher for CorsHeaders {
-	fn check(&self, _req: &RequestHeader, _res: &ResponseHeader) -> bool {
-		true
-	}
-
-	fn call<'a>(
-		&'a self,
-		req: &'a mut Request,
-		res: &'a mut Response,
-		_data: &'a Resources,
-	) -> PinnedFuture<'a, chuchi::Result<()>> {
-		let values = &mut res.header.values;
-
-		// if we have a options request this means we need to
-		// answer with access-control-allow-origin
-		if req.header().method == Method::OPTIONS {
-			res.header.status_code = StatusCode::NO_CONTENT;
-			values.insert(ACCESS_CONTROL_ALLOW_METHODS, "POST, PUT");
-		}
-
-		values.insert(ACCESS_CONTROL_ALLOW_ORIGIN, "*");
-		values.insert(ACCESS_CONTROL_ALLOW_HEADERS, "content-type");
-		values.insert(X_XSS_PROTECTION, "0");
-
-		PinnedFuture::new(async move { Ok(()) })
-	}
+#[post("/")]
+fn post_hello() -> &'static str {
+	"Hello, Post!"
 }
 
 #[tokio::main]
@@ -49,8 +20,16 @@ async fn main() {
 		.await
 		.expect("Address could not be parsed");
 
+	server.add_middleware(
+		Cors::new()
+			.allow_origin("http://localhost:8080")
+			.allow_methods([Method::GET, Method::POST, Method::PUT])
+			.allow_headers(["content-type"])
+			.max_age(Duration::from_secs(60 * 10)),
+	);
+
 	server.add_route(hello_world);
-	server.add_catcher(CorsHeaders);
+	server.add_route(post_hello);
 
 	server.run().await.unwrap();
 }
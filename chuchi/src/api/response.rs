@@ -1,7 +1,10 @@
 use crate::{
 	extractor::Extractor,
 	extractor_extract, extractor_prepare, extractor_validate,
-	header::{values::IntoHeaderName, HeaderValue, HeaderValues, StatusCode},
+	header::{
+		values::IntoHeaderName, Cookie, HeaderValue, HeaderValues, StatusCode,
+		SET_COOKIE,
+	},
 	state::StateRefCell,
 };
 
@@ -49,6 +52,13 @@ impl ResponseSettings {
 		self
 	}
 
+	/// Adds a `Set-Cookie` header, keeping the previously set cookies.
+	pub fn cookie(&mut self, cookie: impl Into<Cookie>) -> &mut Self {
+		let cookie = cookie.into();
+		self.headers.append(SET_COOKIE, cookie.to_header_value());
+		self
+	}
+
 	pub fn status(&mut self, status: StatusCode) -> &mut Self {
 		self.status = status;
 		self
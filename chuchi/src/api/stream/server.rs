@@ -17,7 +17,7 @@ use tracing::{error, trace};
 
 use crate::header::{Method, RequestHeader};
 use crate::routes::{
-	HyperRequest, ParamsNames, PathParams, RawRoute, RoutePath,
+	HyperRequest, ParamsNames, PathParams, RawRoute, RouteKind, RoutePath,
 };
 pub use crate::util::PinnedFuture;
 use crate::ws::{self, JsonError, WebSocket};
@@ -95,6 +95,10 @@ impl RawRoute for StreamServer {
 		}
 	}
 
+	fn kind(&self) -> RouteKind {
+		RouteKind::Api
+	}
+
 	fn call<'a>(
 		&'a self,
 		req: &'a mut HyperRequest,
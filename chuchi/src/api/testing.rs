@@ -28,9 +28,10 @@ impl ChuchiSharedApi {
 		self.inner.resources()
 	}
 
-	/// Routes the request to normal routes and returns their result.
+	/// Routes the request through the middlewares to normal routes and
+	/// returns their result.
 	///
-	/// Useful for tests and niche applications.
+	/// See [`ChuchiShared::route`](crate::ChuchiShared::route).
 	///
 	/// Returns None if no route was found matching the request.
 	pub async fn route(
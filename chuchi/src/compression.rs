@@ -0,0 +1,242 @@
+//! Response compression.
+//!
+//! The [`Compression`] middleware compresses responses with gzip, brotli or
+//! zstd, depending on the `Accept-Encoding` header of the request.
+
+use crate::header::{
+	ContentType, Method, Mime, StatusCode, ACCEPT_ENCODING, CONTENT_ENCODING,
+	CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG,
+};
+use crate::routes::{Middleware, Next};
+use crate::util::{add_vary, parse_quality, PinnedFuture};
+use crate::{Body, Request, Resources, Response};
+
+use async_compression::tokio::bufread::{
+	BrotliEncoder, GzipEncoder, ZstdEncoder,
+};
+use tokio::io::BufReader;
+
+/// An encoding which can be used to compress a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Encoding {
+	Gzip,
+	Brotli,
+	Zstd,
+}
+
+impl Encoding {
+	/// The value used in the `Content-Encoding` header.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Gzip => "gzip",
+			Self::Brotli => "br",
+			Self::Zstd => "zstd",
+		}
+	}
+
+	/// Compresses the body.
+	pub fn encode(&self, body: Body) -> Body {
+		let reader = BufReader::new(body.into_async_reader());
+
+		match self {
+			Self::Gzip => Body::from_async_reader(GzipEncoder::new(reader)),
+			Self::Brotli => Body::from_async_reader(BrotliEncoder::new(reader)),
+			Self::Zstd => Body::from_async_reader(ZstdEncoder::new(reader)),
+		}
+	}
+}
+
+/// A `Middleware` which compresses the responses of every route.
+///
+/// The encoding is negotiated with the `Accept-Encoding` header, respecting
+/// q-values. Responses which already have a `Content-Encoding`, partial
+/// responses, formats which are already compressed, event streams and bodies
+/// smaller than the minimum size are not compressed. Bodies with an unknown
+/// length are always compressed.
+///
+/// Every response which could be compressed gets a `Vary: Accept-Encoding`
+/// header. Strong etags of compressed responses are turned into weak ones.
+///
+/// ## Example
+/// ```
+/// use chuchi::compression::Compression;
+///
+/// # async fn build() {
+/// let mut server = chuchi::build("127.0.0.1:0").await.unwrap();
+/// server.add_middleware(Compression::new().min_size(1024));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Compression {
+	// sorted by preference of the server
+	encodings: Vec<Encoding>,
+	min_size: usize,
+}
+
+impl Compression {
+	/// Creates a `Compression` middleware which supports every encoding and
+	/// only compresses bodies which are at least 1024 bytes long.
+	pub fn new() -> Self {
+		Self {
+			encodings: vec![Encoding::Brotli, Encoding::Zstd, Encoding::Gzip],
+			min_size: 1024,
+		}
+	}
+
+	/// Sets the encodings which should be used, the first one is preferred
+	/// if the client accepts multiple encodings equally.
+	pub fn encodings(mut self, encodings: &[Encoding]) -> Self {
+		self.encodings = encodings.to_vec();
+		self
+	}
+
+	/// Bodies with a known length smaller than `min_size` don't get
+	/// compressed.
+	pub fn min_size(mut self, min_size: usize) -> Self {
+		self.min_size = min_size;
+		self
+	}
+
+	/// Returns the encoding with the highest q-value the client accepts.
+	///
+	/// Returns `None` if the client does not accept any encoding or prefers
+	/// the identity.
+	pub fn negotiate(&self, accept_encoding: &str) -> Option<Encoding> {
+		let accepted: Vec<(&str, f32)> = accept_encoding
+			.split(',')
+			.filter_map(|item| parse_quality(item.trim()))
+			.collect();
+
+		let quality = |token: &str| {
+			accepted
+				.iter()
+				.find(|(t, _)| t.eq_ignore_ascii_case(token))
+				.or_else(|| accepted.iter().find(|(t, _)| *t == "*"))
+				.map(|(_, q)| *q)
+		};
+
+		let (encoding, q) = self
+			.encodings
+			.iter()
+			.filter_map(|e| Some((*e, quality(e.as_str())?)))
+			// max_by returns the last maximum, so reverse to keep the
+			// preference of the server
+			.rev()
+			.max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+		// the identity is only preferred if the client asks for it
+		let identity = accepted
+			.iter()
+			.find(|(t, _)| t.eq_ignore_ascii_case("identity"))
+			.map(|(_, q)| *q)
+			.unwrap_or(0.0);
+
+		(q > 0.0 && q >= identity).then_some(encoding)
+	}
+
+	fn is_compressible(&self, resp: &Response) -> bool {
+		let header = &resp.header;
+		let status = header.status_code;
+
+		if status.is_informational()
+			|| status == StatusCode::NO_CONTENT
+			|| status == StatusCode::NOT_MODIFIED
+			|| status == StatusCode::PARTIAL_CONTENT
+		{
+			return false;
+		}
+
+		let values = &header.values;
+		if values.get(CONTENT_ENCODING).is_some()
+			|| values.get(CONTENT_RANGE).is_some()
+		{
+			return false;
+		}
+
+		if resp.body.len().is_some_and(|len| len < self.min_size) {
+			return false;
+		}
+
+		match &header.content_type {
+			ContentType::Known(mime) => mime.is_compressible(),
+			ContentType::Unknown(s) => is_compressible_str(s),
+			// the content-type might be set directly in the values
+			ContentType::None => values
+				.get_str(CONTENT_TYPE)
+				.map_or(true, is_compressible_str),
+		}
+	}
+}
+
+impl Default for Compression {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Middleware for Compression {
+	fn call<'a>(
+		&'a self,
+		req: &'a mut Request,
+		_resources: &'a Resources,
+		next: Next<'a>,
+	) -> PinnedFuture<'a, crate::Result<Response>> {
+		PinnedFuture::new(async move {
+			let encoding = req
+				.header()
+				.value(ACCEPT_ENCODING)
+				.and_then(|v| self.negotiate(v));
+			let is_head = req.header().method == Method::HEAD;
+
+			let mut resp = next.run(req).await?;
+
+			if !self.is_compressible(&resp) {
+				return Ok(resp);
+			}
+
+			add_vary(&mut resp.header.values, "accept-encoding");
+
+			let Some(encoding) = encoding else {
+				return Ok(resp);
+			};
+
+			let values = &mut resp.header.values;
+			values.insert(CONTENT_ENCODING, encoding.as_str());
+			values.remove(CONTENT_LENGTH);
+
+			// the compressed body is not byte for byte the same anymore
+			let weak_etag = values
+				.get_str(ETAG)
+				.filter(|etag| etag.starts_with('"'))
+				.map(|etag| format!("W/{etag}"));
+			if let Some(etag) = weak_etag {
+				values.insert(ETAG, etag);
+			}
+
+			if !is_head {
+				resp.body = encoding.encode(resp.body.take());
+			}
+
+			Ok(resp)
+		})
+	}
+}
+
+fn is_compressible_str(content_type: &str) -> bool {
+	let essence = content_type.split(';').next().unwrap_or("").trim();
+
+	// the encoder would buffer the events
+	if essence.eq_ignore_ascii_case("text/event-stream") {
+		return false;
+	}
+
+	match essence.parse::<Mime>() {
+		Ok(mime) => mime.is_compressible(),
+		Err(_) => {
+			!(essence.starts_with("image/")
+				|| essence.starts_with("video/")
+				|| essence.starts_with("audio/"))
+		}
+	}
+}
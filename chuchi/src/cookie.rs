@@ -0,0 +1,307 @@
+//! Signed and private cookies.
+//!
+//! Both need a [`CookieKey`] which has to be added as a resource. Signed
+//! cookies can be read by the client but not modified, private cookies are
+//! encrypted and can neither be read nor modified.
+//!
+//! ## Example
+//! ```
+//! use chuchi::cookie::{CookieKey, SignedCookies};
+//! use chuchi::header::Cookie;
+//! use chuchi::{get, Response};
+//!
+//! #[get("/visit")]
+//! fn visit(cookies: SignedCookies) -> Response {
+//! 	let visits: u32 = cookies
+//! 		.get("visits")
+//! 		.and_then(|v| v.parse().ok())
+//! 		.unwrap_or(0);
+//!
+//! 	let cookie = Cookie::new("visits", (visits + 1).to_string());
+//!
+//! 	Response::builder()
+//! 		.cookie(cookies.sign(cookie))
+//! 		.body(format!("visit number {}", visits + 1))
+//! 		.build()
+//! }
+//!
+//! # async fn build() {
+//! let mut server = chuchi::build("127.0.0.1:0").await.unwrap();
+//! server.add_resource(CookieKey::new(&[42; 32]));
+//! server.add_route(visit);
+//! # }
+//! ```
+
+use crate::extractor::Extractor;
+use crate::header::{Cookie, Cookies};
+
+use std::convert::Infallible;
+use std::fmt;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const NONCE_LEN: usize = 12;
+
+/// The keys used to sign and encrypt cookies.
+///
+/// New cookies are always signed and encrypted with the newest key, old keys
+/// added with `with_old_key` are only used to verify and decrypt cookies,
+/// which allows to rotate the key without invalidating every cookie.
+#[derive(Clone)]
+pub struct CookieKey {
+	// the first key is the newest
+	keys: Vec<Key>,
+}
+
+impl CookieKey {
+	/// Creates a `CookieKey` from a secret which needs to be at least 32
+	/// bytes long.
+	///
+	/// ## Panics
+	/// If the secret is shorter than 32 bytes.
+	#[track_caller]
+	pub fn new(secret: &[u8]) -> Self {
+		Self {
+			keys: vec![Key::derive(secret)],
+		}
+	}
+
+	/// Creates a random `CookieKey`.
+	///
+	/// ## Note
+	/// Every cookie becomes invalid when the server restarts.
+	pub fn generate() -> Self {
+		let secret: [u8; 32] = rand::random();
+		Self::new(&secret)
+	}
+
+	/// Adds an old secret which is still accepted when verifying or
+	/// decrypting cookies.
+	///
+	/// ## Panics
+	/// If the secret is shorter than 32 bytes.
+	#[track_caller]
+	pub fn with_old_key(mut self, secret: &[u8]) -> Self {
+		self.keys.push(Key::derive(secret));
+		self
+	}
+
+	/// Signs the value of a cookie with the given name.
+	pub fn sign(&self, name: &str, value: &str) -> String {
+		let tag = self.keys[0].mac(name, value).finalize().into_bytes();
+		format!("{}.{value}", URL_SAFE_NO_PAD.encode(tag))
+	}
+
+	/// Returns the original value if the signature is valid for any key.
+	pub fn verify(&self, name: &str, signed: &str) -> Option<String> {
+		let (tag, value) = signed.split_once('.')?;
+		let tag = URL_SAFE_NO_PAD.decode(tag).ok()?;
+
+		self.keys
+			.iter()
+			.any(|key| key.mac(name, value).verify_slice(&tag).is_ok())
+			.then(|| value.to_string())
+	}
+
+	/// Encrypts the value of a cookie with the given name.
+	///
+	/// The name is authenticated as well, so the value cannot be moved to
+	/// another cookie.
+	pub fn encrypt(&self, name: &str, value: &str) -> String {
+		let nonce: [u8; NONCE_LEN] = rand::random();
+		let payload = Payload {
+			msg: value.as_bytes(),
+			aad: name.as_bytes(),
+		};
+
+		let encrypted = self.keys[0]
+			.cipher()
+			.encrypt(Nonce::from_slice(&nonce), payload)
+			.expect("encrypting a cookie failed");
+
+		let mut data = nonce.to_vec();
+		data.extend(encrypted);
+
+		URL_SAFE_NO_PAD.encode(data)
+	}
+
+	/// Returns the original value if it can be decrypted with any key.
+	pub fn decrypt(&self, name: &str, encrypted: &str) -> Option<String> {
+		let data = URL_SAFE_NO_PAD.decode(encrypted).ok()?;
+		if data.len() < NONCE_LEN {
+			return None;
+		}
+		let (nonce, msg) = data.split_at(NONCE_LEN);
+
+		let value = self.keys.iter().find_map(|key| {
+			let payload = Payload {
+				msg,
+				aad: name.as_bytes(),
+			};
+
+			key.cipher().decrypt(Nonce::from_slice(nonce), payload).ok()
+		})?;
+
+		String::from_utf8(value).ok()
+	}
+}
+
+impl fmt::Debug for CookieKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		// never print the keys
+		f.debug_struct("CookieKey")
+			.field("keys", &self.keys.len())
+			.finish()
+	}
+}
+
+#[derive(Clone)]
+struct Key {
+	signing: [u8; 32],
+	encryption: [u8; 32],
+}
+
+impl Key {
+	#[track_caller]
+	fn derive(secret: &[u8]) -> Self {
+		assert!(
+			secret.len() >= 32,
+			"a cookie key needs to be at least 32 bytes long"
+		);
+
+		let derive = |purpose: &[u8]| -> [u8; 32] {
+			let mut mac = <HmacSha256 as Mac>::new_from_slice(secret)
+				.expect("hmac accepts any key length");
+			mac.update(purpose);
+			mac.finalize().into_bytes().into()
+		};
+
+		Self {
+			signing: derive(b"chuchi cookie signing"),
+			encryption: derive(b"chuchi cookie encryption"),
+		}
+	}
+
+	fn mac(&self, name: &str, value: &str) -> HmacSha256 {
+		let mut mac = <HmacSha256 as Mac>::new_from_slice(&self.signing)
+			.expect("hmac accepts any key length");
+		mac.update(name.as_bytes());
+		mac.update(b"=");
+		mac.update(value.as_bytes());
+		mac
+	}
+
+	fn cipher(&self) -> Aes256Gcm {
+		Aes256Gcm::new(&self.encryption.into())
+	}
+}
+
+/// The cookies of a request which were signed with the `CookieKey`.
+///
+/// Cookies with an invalid signature are treated as if they don't exist.
+#[derive(Debug, Clone)]
+pub struct SignedCookies<'a> {
+	cookies: Cookies,
+	key: &'a CookieKey,
+}
+
+impl<'a> SignedCookies<'a> {
+	pub fn new(cookies: Cookies, key: &'a CookieKey) -> Self {
+		Self { cookies, key }
+	}
+
+	/// Returns the value of the cookie if it exists and the signature is
+	/// valid.
+	pub fn get(&self, name: &str) -> Option<String> {
+		self.cookies
+			.get_all(name)
+			.find_map(|value| self.key.verify(name, value))
+	}
+
+	/// Signs the value of the cookie, so it can be added to a response.
+	pub fn sign(&self, cookie: impl Into<Cookie>) -> Cookie {
+		let mut cookie = cookie.into();
+		let value = self.key.sign(cookie.name(), cookie.value());
+		cookie.set_value(value);
+		cookie
+	}
+}
+
+impl<'a, R> Extractor<'a, R> for SignedCookies<'a> {
+	type Error = Infallible;
+	type Prepared = Cookies;
+
+	extractor_validate!(|validate| {
+		assert!(
+			validate.resources.exists::<CookieKey>(),
+			"CookieKey not in resources"
+		);
+	});
+
+	extractor_prepare!(|prepare| { Ok(prepare.header.cookies()) });
+
+	extractor_extract!(|extract| {
+		Ok(SignedCookies::new(
+			extract.prepared,
+			extract.resources.get().unwrap(),
+		))
+	});
+}
+
+/// The cookies of a request which were encrypted with the `CookieKey`.
+///
+/// Cookies which cannot be decrypted are treated as if they don't exist.
+#[derive(Debug, Clone)]
+pub struct PrivateCookies<'a> {
+	cookies: Cookies,
+	key: &'a CookieKey,
+}
+
+impl<'a> PrivateCookies<'a> {
+	pub fn new(cookies: Cookies, key: &'a CookieKey) -> Self {
+		Self { cookies, key }
+	}
+
+	/// Returns the decrypted value of the cookie if it exists.
+	pub fn get(&self, name: &str) -> Option<String> {
+		self.cookies
+			.get_all(name)
+			.find_map(|value| self.key.decrypt(name, value))
+	}
+
+	/// Encrypts the value of the cookie, so it can be added to a response.
+	pub fn encrypt(&self, cookie: impl Into<Cookie>) -> Cookie {
+		let mut cookie = cookie.into();
+		let value = self.key.encrypt(cookie.name(), cookie.value());
+		cookie.set_value(value);
+		cookie
+	}
+}
+
+impl<'a, R> Extractor<'a, R> for PrivateCookies<'a> {
+	type Error = Infallible;
+	type Prepared = Cookies;
+
+	extractor_validate!(|validate| {
+		assert!(
+			validate.resources.exists::<CookieKey>(),
+			"CookieKey not in resources"
+		);
+	});
+
+	extractor_prepare!(|prepare| { Ok(prepare.header.cookies()) });
+
+	extractor_extract!(|extract| {
+		Ok(PrivateCookies::new(
+			extract.prepared,
+			extract.resources.get().unwrap(),
+		))
+	});
+}
@@ -0,0 +1,367 @@
+//! Cross-Origin Resource Sharing.
+//!
+//! The [`Cors`] middleware answers preflight requests before they reach the
+//! routes and adds the CORS headers to the responses of allowed origins.
+//!
+//! ## Example
+//! ```
+//! use chuchi::cors::Cors;
+//! use chuchi::header::Method;
+//!
+//! use std::time::Duration;
+//!
+//! # async fn build() {
+//! let mut server = chuchi::build("127.0.0.1:0").await.unwrap();
+//! server.add_middleware(
+//! 	Cors::new()
+//! 		.allow_origins(["https://example.com", "https://admin.example.com"])
+//! 		.allow_methods([Method::GET, Method::POST, Method::DELETE])
+//! 		.allow_headers(["content-type", "authorization"])
+//! 		.allow_credentials()
+//! 		.max_age(Duration::from_secs(60 * 60)),
+//! );
+//! # }
+//! ```
+
+use crate::header::{
+	HeaderValue, HeaderValues, Method, RequestHeader, StatusCode,
+	ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+	ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+	ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE,
+	ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN,
+};
+use crate::routes::{Middleware, Next};
+use crate::util::{add_vary, PinnedFuture};
+use crate::{Request, Resources, Response};
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A `Middleware` which implements CORS.
+///
+/// Requests without an `Origin` header are passed on unchanged. Preflight
+/// requests are answered directly with `204 No Content`, or with
+/// `403 Forbidden` if the origin, the method or a header is not allowed.
+///
+/// The matched origin is echoed in `Access-Control-Allow-Origin` together
+/// with `Vary: Origin`, only `allow_any_origin` without credentials sends
+/// `*`.
+///
+/// By default no origin is allowed, the methods `GET`, `HEAD` and `POST`
+/// and no additional headers.
+#[derive(Clone)]
+pub struct Cors {
+	origins: Origins,
+	methods: Vec<Method>,
+	headers: Headers,
+	expose_headers: Vec<String>,
+	credentials: bool,
+	max_age: Option<Duration>,
+}
+
+#[derive(Clone)]
+enum Origins {
+	Any,
+	List(Vec<String>),
+	Predicate(Arc<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+#[derive(Debug, Clone)]
+enum Headers {
+	Any,
+	// lowercase
+	List(Vec<String>),
+}
+
+impl Cors {
+	pub fn new() -> Self {
+		Self {
+			origins: Origins::List(vec![]),
+			methods: vec![Method::GET, Method::HEAD, Method::POST],
+			headers: Headers::List(vec![]),
+			expose_headers: vec![],
+			credentials: false,
+			max_age: None,
+		}
+	}
+
+	/// Allows every origin.
+	///
+	/// ## Panics
+	/// If credentials are allowed.
+	#[track_caller]
+	pub fn allow_any_origin(mut self) -> Self {
+		assert!(
+			!self.credentials,
+			"credentials cannot be allowed for any origin"
+		);
+		self.origins = Origins::Any;
+		self
+	}
+
+	/// Allows an origin, for example `https://example.com`.
+	///
+	/// Can be called multiple times and replaces `allow_any_origin` and
+	/// `allow_origin_fn`.
+	pub fn allow_origin(mut self, origin: impl Into<String>) -> Self {
+		let origin = origin.into();
+		match &mut self.origins {
+			Origins::List(list) => list.push(origin),
+			_ => self.origins = Origins::List(vec![origin]),
+		}
+		self
+	}
+
+	/// Allows every origin in the list.
+	pub fn allow_origins<I>(self, origins: I) -> Self
+	where
+		I: IntoIterator,
+		I::Item: Into<String>,
+	{
+		origins.into_iter().fold(self, Self::allow_origin)
+	}
+
+	/// Allows every origin for which the function returns `true`.
+	///
+	/// Replaces all previously allowed origins.
+	pub fn allow_origin_fn<F>(mut self, f: F) -> Self
+	where
+		F: Fn(&str) -> bool + Send + Sync + 'static,
+	{
+		self.origins = Origins::Predicate(Arc::new(f));
+		self
+	}
+
+	/// Sets the allowed methods.
+	pub fn allow_methods<I>(mut self, methods: I) -> Self
+	where
+		I: IntoIterator<Item = Method>,
+	{
+		self.methods = methods.into_iter().collect();
+		self
+	}
+
+	/// Sets the headers the client is allowed to send.
+	pub fn allow_headers<I>(mut self, headers: I) -> Self
+	where
+		I: IntoIterator,
+		I::Item: AsRef<str>,
+	{
+		let headers = headers
+			.into_iter()
+			.map(|h| h.as_ref().to_ascii_lowercase())
+			.collect();
+		self.headers = Headers::List(headers);
+		self
+	}
+
+	/// Allows the client to send any header.
+	pub fn allow_any_header(mut self) -> Self {
+		self.headers = Headers::Any;
+		self
+	}
+
+	/// Sets the headers of a response which the client is allowed to read.
+	pub fn expose_headers<I>(mut self, headers: I) -> Self
+	where
+		I: IntoIterator,
+		I::Item: Into<String>,
+	{
+		self.expose_headers = headers.into_iter().map(Into::into).collect();
+		self
+	}
+
+	/// Allows the client to send cookies and authorization headers.
+	///
+	/// ## Panics
+	/// If any origin is allowed.
+	#[track_caller]
+	pub fn allow_credentials(mut self) -> Self {
+		assert!(
+			!matches!(self.origins, Origins::Any),
+			"credentials cannot be allowed for any origin"
+		);
+		self.credentials = true;
+		self
+	}
+
+	/// Sets how long the client may cache the result of a preflight request.
+	pub fn max_age(mut self, max_age: Duration) -> Self {
+		self.max_age = Some(max_age);
+		self
+	}
+
+	/// Returns `true` if the origin is allowed.
+	pub fn is_origin_allowed(&self, origin: &str) -> bool {
+		match &self.origins {
+			Origins::Any => true,
+			Origins::List(list) => {
+				list.iter().any(|o| o.eq_ignore_ascii_case(origin))
+			}
+			Origins::Predicate(f) => f(origin),
+		}
+	}
+
+	fn is_method_allowed(&self, method: &str) -> bool {
+		self.methods.iter().any(|m| m.as_str() == method)
+	}
+
+	fn are_headers_allowed(&self, headers: &str) -> bool {
+		match &self.headers {
+			Headers::Any => true,
+			Headers::List(list) => headers
+				.split(',')
+				.map(str::trim)
+				.filter(|h| !h.is_empty())
+				.all(|h| list.iter().any(|l| l.eq_ignore_ascii_case(h))),
+		}
+	}
+
+	/// Adds the headers which are sent with every response to an allowed
+	/// origin.
+	fn add_origin(&self, origin: &HeaderValue, values: &mut HeaderValues) {
+		if matches!(self.origins, Origins::Any) {
+			values.insert(ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+		} else {
+			values.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+		}
+
+		if self.credentials {
+			values.insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, "true");
+		}
+	}
+
+	fn add_vary_origin(&self, values: &mut HeaderValues) {
+		// the response does not depend on the origin if every origin gets `*`
+		if !matches!(self.origins, Origins::Any) {
+			add_vary(values, "origin");
+		}
+	}
+
+	fn preflight(
+		&self,
+		header: &RequestHeader,
+		origin: &HeaderValue,
+		origin_allowed: bool,
+	) -> Response {
+		let values = &header.values;
+		let method = values.get_str(ACCESS_CONTROL_REQUEST_METHOD);
+		let req_headers = values.get_str(ACCESS_CONTROL_REQUEST_HEADERS);
+
+		let allowed = origin_allowed
+			&& method.is_some_and(|m| self.is_method_allowed(m))
+			&& req_headers.map_or(true, |h| self.are_headers_allowed(h));
+
+		let mut resp = Response::builder()
+			.status_code(if allowed {
+				StatusCode::NO_CONTENT
+			} else {
+				StatusCode::FORBIDDEN
+			})
+			.build();
+
+		let resp_values = &mut resp.header.values;
+		self.add_vary_origin(resp_values);
+		add_vary(resp_values, "access-control-request-method");
+		add_vary(resp_values, "access-control-request-headers");
+
+		if !allowed {
+			return resp;
+		}
+
+		self.add_origin(origin, resp_values);
+
+		let methods: Vec<_> = self.methods.iter().map(Method::as_str).collect();
+		resp_values.insert(ACCESS_CONTROL_ALLOW_METHODS, methods.join(", "));
+
+		match (&self.headers, req_headers) {
+			// mirror the requested headers since every one is allowed
+			(Headers::Any, Some(h)) => {
+				resp_values.insert(ACCESS_CONTROL_ALLOW_HEADERS, h.to_string());
+			}
+			(Headers::List(list), _) if !list.is_empty() => {
+				resp_values
+					.insert(ACCESS_CONTROL_ALLOW_HEADERS, list.join(", "));
+			}
+			_ => {}
+		}
+
+		if let Some(max_age) = self.max_age {
+			resp_values.insert(ACCESS_CONTROL_MAX_AGE, max_age.as_secs());
+		}
+
+		resp
+	}
+}
+
+impl Default for Cors {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl fmt::Debug for Cors {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let origins: &dyn fmt::Debug = match &self.origins {
+			Origins::Any => &"Any",
+			Origins::List(list) => list,
+			Origins::Predicate(_) => &"Predicate",
+		};
+
+		f.debug_struct("Cors")
+			.field("origins", origins)
+			.field("methods", &self.methods)
+			.field("headers", &self.headers)
+			.field("expose_headers", &self.expose_headers)
+			.field("credentials", &self.credentials)
+			.field("max_age", &self.max_age)
+			.finish()
+	}
+}
+
+impl Middleware for Cors {
+	fn call<'a>(
+		&'a self,
+		req: &'a mut Request,
+		_resources: &'a Resources,
+		next: Next<'a>,
+	) -> PinnedFuture<'a, crate::Result<Response>> {
+		PinnedFuture::new(async move {
+			let header = req.header();
+
+			// not a cors request
+			let Some(origin) = header.values.get(ORIGIN).cloned() else {
+				return next.run(req).await;
+			};
+
+			// an origin which is not valid utf8 is never allowed
+			let allowed =
+				origin.to_str().is_ok_and(|o| self.is_origin_allowed(o));
+
+			let is_preflight = header.method == Method::OPTIONS
+				&& header.values.get(ACCESS_CONTROL_REQUEST_METHOD).is_some();
+			if is_preflight {
+				return Ok(self.preflight(header, &origin, allowed));
+			}
+
+			let mut resp = next.run(req).await?;
+			let values = &mut resp.header.values;
+
+			self.add_vary_origin(values);
+
+			if allowed {
+				self.add_origin(&origin, values);
+
+				if !self.expose_headers.is_empty() {
+					values.insert(
+						ACCESS_CONTROL_EXPOSE_HEADERS,
+						self.expose_headers.join(", "),
+					);
+				}
+			}
+
+			Ok(resp)
+		})
+	}
+}
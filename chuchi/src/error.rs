@@ -150,7 +150,8 @@ error_kind!( ClientErrorKind,
 	RequestURITooLarge => URI_TOO_LONG,
 	UnsupportedMediaType => UNSUPPORTED_MEDIA_TYPE,
 	RequestedRangeNotSatisfiable => RANGE_NOT_SATISFIABLE,
-	ExpectationFailed => EXPECTATION_FAILED
+	ExpectationFailed => EXPECTATION_FAILED,
+	UnprocessableEntity => UNPROCESSABLE_ENTITY
 );
 
 impl ClientErrorKind {
@@ -0,0 +1,132 @@
+use super::ExtractorError;
+
+use crate::error::{ClientErrorKind, ErrorKind, ServerErrorKind};
+use crate::header::CONTENT_TYPE;
+
+use std::error::Error as StdError;
+use std::io;
+use std::ops::{Deref, DerefMut};
+
+use serde::de::DeserializeOwned;
+
+const FORM_URLENCODED: &str = "application/x-www-form-urlencoded";
+
+/// Deserializes an `application/x-www-form-urlencoded` body, like it is sent
+/// by a html form.
+///
+/// Repeated keys can be deserialized into a `Vec`. If the `Content-Type` does
+/// not match a `415 Unsupported Media Type` is returned, if the body cannot be
+/// deserialized a `422 Unprocessable Entity`. The size limit and timeout of
+/// the request apply.
+///
+/// ## Example
+/// ```
+/// use chuchi::extractor::Form;
+/// use chuchi::post;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Login {
+/// 	username: String,
+/// 	password: String,
+/// 	#[serde(default)]
+/// 	roles: Vec<String>,
+/// }
+///
+/// #[post("/login")]
+/// fn login(form: Form<Login>) -> String {
+/// 	format!("{} with {} roles", form.username, form.roles.len())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Form<T>(pub T);
+
+impl<T> Form<T> {
+	pub fn into_inner(self) -> T {
+		self.0
+	}
+}
+
+impl<T> Deref for Form<T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T> DerefMut for Form<T> {
+	fn deref_mut(&mut self) -> &mut T {
+		&mut self.0
+	}
+}
+
+impl<'a, T, R> super::Extractor<'a, R> for Form<T>
+where
+	T: DeserializeOwned + Send + 'a,
+{
+	type Error = FormError;
+	type Prepared = T;
+
+	extractor_validate!();
+
+	extractor_prepare!(|prepare| {
+		let content_type = prepare.header.value(CONTENT_TYPE);
+		// parameters like charset are ignored
+		let essence = content_type
+			.and_then(|ct| ct.split(';').next())
+			.map(str::trim);
+
+		if !essence.is_some_and(|e| e.eq_ignore_ascii_case(FORM_URLENCODED)) {
+			return Err(FormError::UnsupportedMediaType(
+				content_type.map(Into::into),
+			));
+		}
+
+		let mut prepare = prepare;
+		let body = prepare.take_body().ok_or(FormError::NoBody)?;
+		let bytes = body.into_bytes().await.map_err(FormError::Read)?;
+
+		let de = serde_html_form::Deserializer::from_bytes(&bytes);
+		serde_path_to_error::deserialize(de).map_err(|e| FormError::Form {
+			path: e.path().to_string(),
+			error: e.into_inner(),
+		})
+	});
+
+	extractor_extract!(|extract| { Ok(Form(extract.prepared)) });
+}
+
+/// The body of the request could not be deserialized as a form.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum FormError {
+	#[error("Expected content-type {FORM_URLENCODED} got {0:?}")]
+	UnsupportedMediaType(Option<String>),
+	#[error("The route does not provide a body")]
+	NoBody,
+	#[error("Failed to read the body: {0}")]
+	Read(io::Error),
+	#[error("Failed to deserialize form at {path}: {error}")]
+	Form {
+		/// The path of the field which failed, for example `roles`.
+		path: String,
+		error: serde_html_form::de::Error,
+	},
+}
+
+impl ExtractorError for FormError {
+	fn error_kind(&self) -> ErrorKind {
+		match self {
+			Self::UnsupportedMediaType(_) => {
+				ClientErrorKind::UnsupportedMediaType.into()
+			}
+			Self::NoBody => ServerErrorKind::InternalServerError.into(),
+			Self::Read(e) => ClientErrorKind::from_io(e).into(),
+			Self::Form { .. } => ClientErrorKind::UnprocessableEntity.into(),
+		}
+	}
+
+	fn into_std(self) -> Box<dyn StdError + Send + Sync> {
+		Box::new(self)
+	}
+}
@@ -6,14 +6,35 @@ use std::str::FromStr;
 use std::{future::Future, ops::Deref};
 
 use crate::error::{ClientErrorKind, ErrorKind, ServerErrorKind};
-use crate::header::RequestHeader;
+use crate::header::{Cookies, RequestHeader};
 use crate::state::StateValidation;
 use crate::{
 	routes::{ParamsNames, PathParams},
 	state::State,
-	Request, Resources,
+	Body, Request, Resources,
 };
 
+#[cfg(feature = "query")]
+mod query;
+#[cfg(feature = "query")]
+#[cfg_attr(docsrs, doc(cfg(feature = "query")))]
+pub use query::{Query, QueryError};
+
+#[cfg(feature = "form")]
+mod form;
+#[cfg(feature = "form")]
+#[cfg_attr(docsrs, doc(cfg(feature = "form")))]
+pub use form::{Form, FormError};
+
+#[cfg(feature = "multipart")]
+mod multipart;
+#[cfg(all(feature = "multipart", feature = "fs"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "multipart", feature = "fs"))))]
+pub use multipart::TempFile;
+#[cfg(feature = "multipart")]
+#[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+pub use multipart::{Multipart, MultipartError, Part};
+
 #[non_exhaustive]
 pub struct Validate<'a> {
 	pub name: &'a str,
@@ -26,6 +47,9 @@ pub struct Validate<'a> {
 pub struct Prepare<'a> {
 	pub name: &'a str,
 	pub header: &'a RequestHeader,
+	/// The body of the request, `None` if the route does not provide one,
+	/// for example a websocket.
+	pub body: Option<&'a mut Body>,
 	pub params: &'a PathParams,
 	pub state: &'a mut State,
 	pub resources: &'a Resources,
@@ -141,11 +165,25 @@ impl<'a> Prepare<'a> {
 		Self {
 			name,
 			header,
+			body: None,
 			params,
 			state,
 			resources,
 		}
 	}
+
+	/// Allows extractors to read the body of the request.
+	pub fn with_body(mut self, body: &'a mut Body) -> Self {
+		self.body = Some(body);
+		self
+	}
+
+	/// Takes the body of the request leaving an empty one.
+	///
+	/// Returns `None` if the route does not provide a body.
+	pub fn take_body(&mut self) -> Option<Body> {
+		self.body.as_mut().map(|body| body.take())
+	}
 }
 
 impl<'a, 'b, P, R> Extract<'a, 'b, P, R> {
@@ -233,6 +271,18 @@ impl<'a, R> Extractor<'a, R> for &'a RequestHeader {
 	extractor_extract!(|extract| { Ok(extract.state.get().unwrap()) });
 }
 
+/// Parses the `Cookie` headers of the request.
+impl<'a, R> Extractor<'a, R> for Cookies {
+	type Error = Infallible;
+	type Prepared = Cookies;
+
+	extractor_validate!();
+
+	extractor_prepare!(|prepare| { Ok(prepare.header.cookies()) });
+
+	extractor_extract!(|extract| { Ok(extract.prepared) });
+}
+
 pub type PathStr = PathParam<str>;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
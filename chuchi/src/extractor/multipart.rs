@@ -0,0 +1,315 @@
+use super::ExtractorError;
+
+use crate::error::{ClientErrorKind, ErrorKind, ServerErrorKind};
+use crate::header::CONTENT_TYPE;
+use crate::Body;
+
+use std::error::Error as StdError;
+use std::io;
+
+use bytes::{Bytes, BytesMut};
+use hyper::HeaderMap;
+
+/// Streams the parts of a `multipart/form-data` body.
+///
+/// Parts are read one after another without buffering the whole body. The
+/// size limit and timeout of the request apply to the whole body, a limit for
+/// every part can be set with `Multipart::set_part_size_limit`.
+///
+/// ## Note
+/// The default request size limit is only 4 KiB, to accept uploads it needs
+/// to be raised with `Chuchi::request_size_limit` or with
+/// `Request::set_size_limit` in a middleware.
+///
+/// ## Example
+/// ```
+/// use chuchi::extractor::Multipart;
+/// use chuchi::post;
+///
+/// #[post("/upload")]
+/// async fn upload(mut multipart: Multipart) -> chuchi::Result<String> {
+/// 	multipart.set_part_size_limit(Some(1024 * 1024));
+///
+/// 	let mut names = vec![];
+/// 	while let Some(part) = multipart.next_part().await? {
+/// 		names.push(part.file_name().unwrap_or("").to_string());
+/// 		let _data = part.bytes().await?;
+/// 	}
+///
+/// 	Ok(names.join(","))
+/// }
+/// ```
+pub struct Multipart {
+	inner: multer::Multipart<'static>,
+	part_size_limit: Option<usize>,
+}
+
+impl Multipart {
+	/// Creates a `Multipart` from a body and the value of the `Content-Type`
+	/// header.
+	pub fn new(body: Body, content_type: &str) -> Result<Self, MultipartError> {
+		let boundary = multer::parse_boundary(content_type).map_err(|_| {
+			MultipartError::UnsupportedMediaType(Some(content_type.into()))
+		})?;
+
+		Ok(Self {
+			inner: multer::Multipart::new(
+				body.into_async_bytes_streamer(),
+				boundary,
+			),
+			part_size_limit: None,
+		})
+	}
+
+	/// Sets the maximum size of every part which get's returned after calling
+	/// this.
+	pub fn set_part_size_limit(&mut self, limit: Option<usize>) {
+		self.part_size_limit = limit;
+	}
+
+	/// Returns the next part or `None` if all parts were read.
+	///
+	/// The previous part get's skipped if it was not read to the end.
+	pub async fn next_part(&mut self) -> Result<Option<Part>, MultipartError> {
+		let field = self.inner.next_field().await?;
+
+		Ok(field.map(|inner| Part {
+			inner,
+			size_limit: self.part_size_limit,
+			read: 0,
+		}))
+	}
+}
+
+impl<'a, R> super::Extractor<'a, R> for Multipart {
+	type Error = MultipartError;
+	type Prepared = Multipart;
+
+	extractor_validate!();
+
+	extractor_prepare!(|prepare| {
+		let content_type = prepare
+			.header
+			.value(CONTENT_TYPE)
+			.ok_or(MultipartError::UnsupportedMediaType(None))?;
+
+		let mut prepare = prepare;
+		let body = prepare.take_body().ok_or(MultipartError::NoBody)?;
+
+		Multipart::new(body, content_type)
+	});
+
+	extractor_extract!(|extract| { Ok(extract.prepared) });
+}
+
+/// A single part of a `Multipart` body.
+pub struct Part {
+	inner: multer::Field<'static>,
+	size_limit: Option<usize>,
+	read: usize,
+}
+
+impl Part {
+	/// The name of the form field.
+	pub fn name(&self) -> Option<&str> {
+		self.inner.name()
+	}
+
+	/// The file name of an uploaded file.
+	pub fn file_name(&self) -> Option<&str> {
+		self.inner.file_name()
+	}
+
+	/// The value of the `Content-Type` header of the part.
+	pub fn content_type(&self) -> Option<&str> {
+		self.headers().get(CONTENT_TYPE)?.to_str().ok()
+	}
+
+	/// All headers of the part.
+	pub fn headers(&self) -> &HeaderMap {
+		self.inner.headers()
+	}
+
+	/// Returns the next chunk of data or `None` if the part was read to the
+	/// end.
+	pub async fn chunk(&mut self) -> Result<Option<Bytes>, MultipartError> {
+		let Some(chunk) = self.inner.chunk().await? else {
+			return Ok(None);
+		};
+
+		self.read += chunk.len();
+		match self.size_limit {
+			Some(limit) if self.read > limit => {
+				Err(MultipartError::PartTooLarge(limit))
+			}
+			_ => Ok(Some(chunk)),
+		}
+	}
+
+	/// Reads the whole part into memory.
+	pub async fn bytes(mut self) -> Result<Bytes, MultipartError> {
+		let mut bytes = BytesMut::new();
+		while let Some(chunk) = self.chunk().await? {
+			bytes.extend_from_slice(&chunk);
+		}
+
+		Ok(bytes.freeze())
+	}
+
+	/// Reads the whole part into a string.
+	pub async fn text(self) -> Result<String, MultipartError> {
+		let bytes = self.bytes().await?;
+
+		String::from_utf8(bytes.into()).map_err(|e| {
+			MultipartError::Io(io::Error::new(io::ErrorKind::InvalidData, e))
+		})
+	}
+
+	/// Writes the part to a new file in the temporary directory.
+	///
+	/// The file get's deleted when the `TempFile` is dropped, except if
+	/// `TempFile::persist` was called.
+	#[cfg(feature = "fs")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+	pub async fn into_temp_file(mut self) -> Result<TempFile, MultipartError> {
+		use tokio::io::AsyncWriteExt;
+
+		let (file, mut fs_file) = TempFile::create().await?;
+
+		while let Some(chunk) = self.chunk().await? {
+			fs_file.write_all(&chunk).await?;
+		}
+		fs_file.flush().await?;
+
+		Ok(file)
+	}
+}
+
+/// A file in the temporary directory which get's deleted on drop.
+#[cfg(feature = "fs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+#[derive(Debug)]
+pub struct TempFile {
+	path: std::path::PathBuf,
+	persisted: bool,
+}
+
+#[cfg(feature = "fs")]
+impl TempFile {
+	/// Creates a new file with a random name, never opening an existing one.
+	async fn create() -> io::Result<(Self, tokio::fs::File)> {
+		use rand::distributions::Alphanumeric;
+		use rand::{thread_rng, Rng};
+
+		loop {
+			let name: String = thread_rng()
+				.sample_iter(&Alphanumeric)
+				.take(16)
+				.map(char::from)
+				.collect();
+			let path =
+				std::env::temp_dir().join(format!("chuchi-upload-{name}"));
+
+			let res = tokio::fs::OpenOptions::new()
+				.write(true)
+				.create_new(true)
+				.open(&path)
+				.await;
+
+			match res {
+				Ok(file) => {
+					let temp = Self {
+						path,
+						persisted: false,
+					};
+					return Ok((temp, file));
+				}
+				Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+				Err(e) => return Err(e),
+			}
+		}
+	}
+
+	pub fn path(&self) -> &std::path::Path {
+		&self.path
+	}
+
+	/// Moves the file to the given path, so it does not get deleted.
+	pub async fn persist(
+		mut self,
+		to: impl AsRef<std::path::Path>,
+	) -> io::Result<()> {
+		let to = to.as_ref();
+
+		// renaming fails if the paths are on different filesystems
+		if tokio::fs::rename(&self.path, to).await.is_err() {
+			tokio::fs::copy(&self.path, to).await?;
+			tokio::fs::remove_file(&self.path).await?;
+		}
+
+		self.persisted = true;
+
+		Ok(())
+	}
+}
+
+#[cfg(feature = "fs")]
+impl Drop for TempFile {
+	fn drop(&mut self) {
+		if !self.persisted {
+			let _ = std::fs::remove_file(&self.path);
+		}
+	}
+}
+
+/// The body of the request could not be read as multipart.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum MultipartError {
+	#[error("Expected content-type multipart/form-data got {0:?}")]
+	UnsupportedMediaType(Option<String>),
+	#[error("The route does not provide a body")]
+	NoBody,
+	#[error("A part is bigger than {0} bytes")]
+	PartTooLarge(usize),
+	#[error("Failed to read multipart: {0}")]
+	Multipart(#[from] multer::Error),
+	#[error("Io error: {0}")]
+	Io(#[from] io::Error),
+}
+
+impl ExtractorError for MultipartError {
+	fn error_kind(&self) -> ErrorKind {
+		match self {
+			Self::UnsupportedMediaType(_) => {
+				ClientErrorKind::UnsupportedMediaType.into()
+			}
+			Self::NoBody => ServerErrorKind::InternalServerError.into(),
+			Self::PartTooLarge(_) => {
+				ClientErrorKind::RequestEntityTooLarge.into()
+			}
+			// the size limit or timeout of the body was reached
+			Self::Multipart(multer::Error::StreamReadFailed(e)) => {
+				match e.downcast_ref::<io::Error>() {
+					Some(e) => ClientErrorKind::from_io(e).into(),
+					None => ClientErrorKind::BadRequest.into(),
+				}
+			}
+			Self::Multipart(_) => ClientErrorKind::BadRequest.into(),
+			Self::Io(e) if e.kind() == io::ErrorKind::InvalidData => {
+				ClientErrorKind::BadRequest.into()
+			}
+			Self::Io(_) => ServerErrorKind::InternalServerError.into(),
+		}
+	}
+
+	fn into_std(self) -> Box<dyn StdError + Send + Sync> {
+		Box::new(self)
+	}
+}
+
+impl From<MultipartError> for crate::Error {
+	fn from(e: MultipartError) -> Self {
+		Self::new(e.error_kind(), e)
+	}
+}
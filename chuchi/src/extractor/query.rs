@@ -0,0 +1,143 @@
+use super::ExtractorError;
+
+use crate::error::{ClientErrorKind, ErrorKind};
+use crate::header::RequestHeader;
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use serde::de::DeserializeOwned;
+
+/// Deserializes the query string of the request.
+///
+/// If the query cannot be deserialized a `400 Bad Request` is returned. Use
+/// `Option<Query<T>>` to get `None` if the request has no query string.
+///
+/// ## Example
+/// ```
+/// use chuchi::extractor::Query;
+/// use chuchi::get;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Search {
+/// 	q: String,
+/// 	page: Option<u32>,
+/// }
+///
+/// #[get("/search")]
+/// fn search(query: Query<Search>) -> String {
+/// 	format!("{} on page {}", query.q, query.page.unwrap_or(1))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Query<T>(pub T);
+
+impl<T> Query<T> {
+	pub fn into_inner(self) -> T {
+		self.0
+	}
+}
+
+impl<T> Query<T>
+where
+	T: DeserializeOwned,
+{
+	/// Deserializes the query string of the header, a missing query is
+	/// treated as an empty string.
+	pub fn from_header(header: &RequestHeader) -> Result<Self, QueryError> {
+		let query = header.uri().query().unwrap_or("");
+		let de = serde_urlencoded::Deserializer::new(form_urlencoded::parse(
+			query.as_bytes(),
+		));
+
+		serde_path_to_error::deserialize(de)
+			.map(Query)
+			.map_err(QueryError)
+	}
+}
+
+impl<T> Deref for Query<T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T> DerefMut for Query<T> {
+	fn deref_mut(&mut self) -> &mut T {
+		&mut self.0
+	}
+}
+
+impl<'a, T, R> super::Extractor<'a, R> for Query<T>
+where
+	T: DeserializeOwned + Send + 'a,
+{
+	type Error = QueryError;
+	type Prepared = T;
+
+	extractor_validate!();
+
+	extractor_prepare!(|prepare| {
+		Query::from_header(prepare.header).map(Query::into_inner)
+	});
+
+	extractor_extract!(|extract| { Ok(Query(extract.prepared)) });
+}
+
+impl<'a, T, R> super::Extractor<'a, R> for Option<Query<T>>
+where
+	T: DeserializeOwned + Send + 'a,
+{
+	type Error = QueryError;
+	type Prepared = Option<T>;
+
+	extractor_validate!();
+
+	extractor_prepare!(|prepare| {
+		match prepare.header.uri().query() {
+			Some(q) if !q.is_empty() => {
+				Query::from_header(prepare.header).map(|q| Some(q.0))
+			}
+			_ => Ok(None),
+		}
+	});
+
+	extractor_extract!(|extract| { Ok(extract.prepared.map(Query)) });
+}
+
+/// The query string could not be deserialized.
+#[derive(Debug)]
+pub struct QueryError(serde_path_to_error::Error<serde_urlencoded::de::Error>);
+
+impl QueryError {
+	/// The path of the field which failed to deserialize, for example
+	/// `page`.
+	pub fn path(&self) -> String {
+		self.0.path().to_string()
+	}
+}
+
+impl fmt::Display for QueryError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Failed to deserialize query: {}", self.0)
+	}
+}
+
+impl StdError for QueryError {
+	fn source(&self) -> Option<&(dyn StdError + 'static)> {
+		Some(self.0.inner())
+	}
+}
+
+impl ExtractorError for QueryError {
+	fn error_kind(&self) -> ErrorKind {
+		ErrorKind::Client(ClientErrorKind::BadRequest)
+	}
+
+	fn into_std(self) -> Box<dyn StdError + Send + Sync> {
+		Box::new(self)
+	}
+}
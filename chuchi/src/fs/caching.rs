@@ -1,23 +1,35 @@
+use crate::header::values::HeaderName;
 use crate::header::{
-	RequestHeader, ResponseHeader, StatusCode, CACHE_CONTROL, ETAG,
-	IF_NONE_MATCH,
+	Method, RequestHeader, ResponseHeader, StatusCode, CACHE_CONTROL, ETAG,
+	IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, IF_UNMODIFIED_SINCE,
+	LAST_MODIFIED,
 };
 use crate::into::IntoResponse;
 use crate::Response;
 
+use std::collections::HashMap;
 use std::fmt;
-use std::time::Duration;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
+use tokio::io::{self, AsyncReadExt};
 
 // == 1day
 const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24);
 
+const HASH_OFFSET: u64 = 0xcbf29ce484222325;
+const HASH_PRIME: u64 = 0x100000001b3;
+
+/// An entity tag, including the quotes and the weak prefix `W/`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Etag(String);
 
 impl Etag {
+	/// Creates a random strong etag.
 	pub fn new() -> Self {
 		let rand_str: String = thread_rng()
 			.sample_iter(&Alphanumeric)
@@ -25,12 +37,44 @@ impl Etag {
 			.take(30)
 			.collect();
 
-		Self(rand_str)
+		Self(format!("\"{rand_str}\""))
+	}
+
+	/// Creates a strong etag derived from the content.
+	pub fn from_content(bytes: &[u8]) -> Self {
+		Self::from_hash(bytes.len() as u64, content_hash(bytes))
+	}
+
+	pub(super) fn from_hash(len: u64, hash: u64) -> Self {
+		Self(format!("\"{len:x}-{hash:016x}\""))
 	}
 
 	pub fn as_str(&self) -> &str {
 		self.0.as_str()
 	}
+
+	pub fn is_weak(&self) -> bool {
+		self.0.starts_with("W/")
+	}
+
+	// the tag without the weak prefix
+	fn opaque(&self) -> &str {
+		self.0.trim_start_matches("W/")
+	}
+
+	fn weak_eq(&self, tag: &str) -> bool {
+		self.opaque() == tag
+	}
+
+	fn strong_eq(&self, weak: bool, tag: &str) -> bool {
+		!weak && !self.is_weak() && self.opaque() == tag
+	}
+}
+
+impl Default for Etag {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl fmt::Display for Etag {
@@ -51,11 +95,134 @@ impl PartialEq<&str> for Etag {
 	}
 }
 
+/// Hashes the content of a file, used to create an `Etag`.
+///
+/// This is a const fn so `memory_file!` can hash the file at compile time.
+#[doc(hidden)]
+pub const fn content_hash(bytes: &[u8]) -> u64 {
+	hash_update(HASH_OFFSET, bytes)
+}
+
+// hashing the bytes in multiple chunks returns the same result as long as
+// every chunk except the last is a multiple of 8 bytes long
+const fn hash_update(mut hash: u64, bytes: &[u8]) -> u64 {
+	let len = bytes.len();
+	let mut i = 0;
+
+	// processing 8 bytes at a time keeps the const evaluation fast
+	while i + 8 <= len {
+		let word = u64::from_le_bytes([
+			bytes[i],
+			bytes[i + 1],
+			bytes[i + 2],
+			bytes[i + 3],
+			bytes[i + 4],
+			bytes[i + 5],
+			bytes[i + 6],
+			bytes[i + 7],
+		]);
+		hash = (hash ^ word).wrapping_mul(HASH_PRIME);
+		hash ^= hash >> 29;
+		i += 8;
+	}
+
+	while i < len {
+		hash = (hash ^ bytes[i] as u64).wrapping_mul(HASH_PRIME);
+		i += 1;
+	}
+
+	hash
+}
+
+struct CachedEtag {
+	modified: SystemTime,
+	size: u64,
+	etag: Etag,
+}
+
+// the etags of files on disk, they are only computed again if the
+// modification time or the size changes
+static FILE_ETAGS: OnceLock<Mutex<HashMap<PathBuf, CachedEtag>>> =
+	OnceLock::new();
+
+/// Returns the etag of a file, which gets derived from its content.
+pub(super) async fn file_etag(
+	path: &Path,
+	metadata: &Metadata,
+) -> io::Result<Etag> {
+	let cache = FILE_ETAGS.get_or_init(Default::default);
+	let modified = metadata.modified().ok();
+	let size = metadata.len();
+
+	if let Some(modified) = modified {
+		let cache = cache.lock().unwrap();
+		let cached = cache
+			.get(path)
+			.filter(|c| c.modified == modified && c.size == size);
+
+		if let Some(cached) = cached {
+			return Ok(cached.etag.clone());
+		}
+	}
+
+	let mut file = tokio::fs::File::open(path).await?;
+	let mut buf = vec![0; 64 * 1024];
+	let mut hash = HASH_OFFSET;
+	let mut len = 0;
+
+	loop {
+		// fill the entire buffer so every chunk is a multiple of 8 bytes
+		let mut filled = 0;
+		while filled < buf.len() {
+			match file.read(&mut buf[filled..]).await? {
+				0 => break,
+				n => filled += n,
+			}
+		}
+
+		hash = hash_update(hash, &buf[..filled]);
+		len += filled as u64;
+
+		if filled < buf.len() {
+			break;
+		}
+	}
+
+	let etag = Etag::from_hash(len, hash);
+
+	// without a modification time we cannot know if the file changed
+	if let Some(modified) = modified {
+		cache.lock().unwrap().insert(
+			path.to_path_buf(),
+			CachedEtag {
+				modified,
+				size,
+				etag: etag.clone(),
+			},
+		);
+	}
+
+	Ok(etag)
+}
+
+/// The result of evaluating the conditional headers of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precondition {
+	/// The request should be answered normally.
+	Passed,
+	/// The client already has the current version, answer with
+	/// `304 Not Modified`.
+	NotModified,
+	/// A precondition failed, answer with `412 Precondition Failed`.
+	Failed,
+}
+
 /// Controls if caching information should be sent.
 ///
-/// The Caching struct contains an Etag which stores a random tag which
-/// indentifies a specific file. The Etag gets generated when the struct gets
-/// created.
+/// The Caching struct contains an Etag which identifies a specific version
+/// of a file and optionally the time the file was last modified.
+/// `StaticFiles` and `MemoryFile` derive the Etag from the content of the
+/// file, `Caching::new` generates a random one.
 ///
 /// ## Example
 /// ```ignore
@@ -86,6 +253,7 @@ impl PartialEq<&str> for Etag {
 pub struct Caching {
 	max_age: Duration,
 	etag: Etag,
+	last_modified: Option<SystemTime>,
 }
 
 impl Caching {
@@ -93,6 +261,7 @@ impl Caching {
 		Self {
 			max_age,
 			etag: Etag::new(),
+			last_modified: None,
 		}
 	}
 
@@ -101,13 +270,97 @@ impl Caching {
 		Self::new(DEFAULT_MAX_AGE)
 	}
 
+	pub fn etag(&self) -> &Etag {
+		&self.etag
+	}
+
+	pub fn set_etag(&mut self, etag: Etag) {
+		self.etag = etag;
+	}
+
+	pub fn last_modified(&self) -> Option<SystemTime> {
+		self.last_modified
+	}
+
+	/// Sets the time the content was last modified, which gets sent as
+	/// `Last-Modified`.
+	pub fn set_last_modified(&mut self, time: SystemTime) {
+		// http dates only have a precision of seconds
+		let secs = time
+			.duration_since(UNIX_EPOCH)
+			.map(|d| d.as_secs())
+			.unwrap_or(0);
+		self.last_modified = Some(UNIX_EPOCH + Duration::from_secs(secs));
+	}
+
+	/// Returns `true` if `If-None-Match` matches the Etag.
 	pub fn if_none_match(&self, header: &RequestHeader) -> bool {
 		header
 			.value(IF_NONE_MATCH)
-			.map(|none_match| none_match.len() == 30 && self.etag == none_match)
+			.map(|none_match| {
+				none_match.trim() == "*"
+					|| etag_list(none_match)
+						.any(|(_, tag)| self.etag.weak_eq(tag))
+			})
 			.unwrap_or(false)
 	}
 
+	/// Evaluates `If-Match`, `If-Unmodified-Since`, `If-None-Match` and
+	/// `If-Modified-Since` in the order defined by RFC 9110.
+	pub fn precondition(&self, header: &RequestHeader) -> Precondition {
+		if let Some(if_match) = header.value(IF_MATCH) {
+			let matches = if_match.trim() == "*"
+				|| etag_list(if_match)
+					.any(|(weak, tag)| self.etag.strong_eq(weak, tag));
+
+			if !matches {
+				return Precondition::Failed;
+			}
+		} else if let Some(since) = header_date(header, IF_UNMODIFIED_SINCE) {
+			if self.last_modified.is_some_and(|m| m > since) {
+				return Precondition::Failed;
+			}
+		}
+
+		let is_get = matches!(header.method, Method::GET | Method::HEAD);
+
+		if header.value(IF_NONE_MATCH).is_some() {
+			if self.if_none_match(header) {
+				return if is_get {
+					Precondition::NotModified
+				} else {
+					Precondition::Failed
+				};
+			}
+		} else if let Some(since) = header_date(header, IF_MODIFIED_SINCE) {
+			if is_get && self.last_modified.is_some_and(|m| m <= since) {
+				return Precondition::NotModified;
+			}
+		}
+
+		Precondition::Passed
+	}
+
+	/// Returns `true` if the `Range` of the request should be used, which is
+	/// the case if there is no `If-Range` or it matches the Etag or the last
+	/// modification time.
+	pub fn if_range(&self, header: &RequestHeader) -> bool {
+		let Some(if_range) = header.value(IF_RANGE) else {
+			return true;
+		};
+		let if_range = if_range.trim();
+
+		if if_range.starts_with('"') || if_range.starts_with("W/") {
+			return etag_list(if_range)
+				.next()
+				.is_some_and(|(weak, tag)| self.etag.strong_eq(weak, tag));
+		}
+
+		// a date only matches if it is exactly the same
+		httpdate::parse_http_date(if_range)
+			.is_ok_and(|date| self.last_modified == Some(date))
+	}
+
 	fn cache_control_string(&self) -> String {
 		format!("max-age={}, public", self.max_age.as_secs())
 	}
@@ -118,8 +371,18 @@ impl Caching {
 			.insert(CACHE_CONTROL, self.cache_control_string());
 
 		// etag makes only sense with files not 404
-		if header.status_code == StatusCode::OK {
+		if matches!(
+			header.status_code,
+			StatusCode::OK | StatusCode::PARTIAL_CONTENT
+		) {
 			header.values.insert(ETAG, String::from(self.etag));
+
+			if let Some(last_modified) = self.last_modified {
+				header.values.insert(
+					LAST_MODIFIED,
+					httpdate::fmt_http_date(last_modified),
+				);
+			}
 		}
 	}
 }
@@ -133,3 +396,58 @@ impl IntoResponse for Caching {
 			.build()
 	}
 }
+
+/// Returns the response if the preconditions of the request decide it.
+pub(super) fn precondition_response(
+	caching: Option<&Caching>,
+	header: &RequestHeader,
+) -> Option<Response> {
+	let caching = caching?;
+
+	match caching.precondition(header) {
+		Precondition::Passed => None,
+		Precondition::NotModified => Some(caching.clone().into_response()),
+		Precondition::Failed => Some(StatusCode::PRECONDITION_FAILED.into()),
+	}
+}
+
+/// Returns `true` if the range of the request should be served.
+pub(super) fn use_range(
+	caching: Option<&Caching>,
+	header: &RequestHeader,
+) -> bool {
+	match caching {
+		Some(caching) => caching.if_range(header),
+		// without a validator If-Range can never match
+		None => header.value(IF_RANGE).is_none(),
+	}
+}
+
+fn header_date(header: &RequestHeader, key: HeaderName) -> Option<SystemTime> {
+	httpdate::parse_http_date(header.value(key)?).ok()
+}
+
+/// Parses a list of entity tags, returns if the tag is weak and the tag
+/// including the quotes.
+fn etag_list(value: &str) -> impl Iterator<Item = (bool, &str)> {
+	let mut rest = value;
+
+	std::iter::from_fn(move || {
+		rest = rest.trim_start_matches([' ', '\t', ',']);
+
+		let weak = rest.starts_with("W/");
+		if weak {
+			rest = &rest[2..];
+		}
+
+		if !rest.starts_with('"') {
+			return None;
+		}
+
+		let end = rest[1..].find('"')? + 2;
+		let (tag, remaining) = rest.split_at(end);
+		rest = remaining;
+
+		Some((weak, tag))
+	})
+}
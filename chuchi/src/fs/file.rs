@@ -38,6 +38,18 @@ impl File {
 			.and_then(Mime::from_extension)
 			.unwrap_or(Mime::BINARY);
 
+		Self::open_with_mime(path, mime_type).await
+	}
+
+	/// Like `open` but the `Mime` is not derived from the extension, for
+	/// example for precompressed files.
+	pub(super) async fn open_with_mime<P>(
+		path: P,
+		mime_type: Mime,
+	) -> io::Result<Self>
+	where
+		P: AsRef<Path>,
+	{
 		let file = fs::File::open(path).await?;
 		let metadata = file.metadata().await?;
 
@@ -1,26 +1,32 @@
+use super::caching::{content_hash, precondition_response, use_range};
 use super::static_files::CachingBuilder;
-use super::{file, partial_file, Caching, Range};
+use super::{file, partial_file, Caching, Etag, Range};
 use crate::header::Method;
-use crate::into::IntoResponse;
-use crate::routes::{ParamsNames, PathParams, RoutePath};
+use crate::routes::{ParamsNames, PathParams, RouteKind, RoutePath};
 use crate::util::PinnedFuture;
 use crate::{Error, IntoRoute, Request, Resources, Response, Route};
 
 use std::io;
 use std::time::Duration;
 
+/// The etag of `caching` is used as is, a `MemoryFile` derives it from the
+/// content at compile time.
 pub fn serve_memory_file(
 	path: &'static str,
 	bytes: &'static [u8],
 	req: &Request,
 	caching: Option<Caching>,
 ) -> io::Result<Response> {
-	// check caching and if the etag matches return NOT_MODIFIED
-	if matches!(&caching, Some(c) if c.if_none_match(req.header())) {
-		return Ok(caching.unwrap().into_response());
+	let header = req.header();
+
+	// check the conditional headers, for example if the etag matches return
+	// NOT_MODIFIED
+	if let Some(res) = precondition_response(caching.as_ref(), header) {
+		return Ok(res);
 	}
 
-	let range = Range::parse(req.header());
+	let range =
+		Range::parse(header).filter(|_| use_range(caching.as_ref(), header));
 
 	let mut res = match range {
 		Some(range) => {
@@ -54,6 +60,7 @@ pub struct MemoryFile {
 	uri: &'static str,
 	path: &'static str,
 	bytes: &'static [u8],
+	hash: u64,
 	caching: CachingBuilder,
 }
 
@@ -68,6 +75,7 @@ impl MemoryFile {
 			uri,
 			path,
 			bytes,
+			hash: content_hash(bytes),
 			caching: CachingBuilder::Default,
 		}
 	}
@@ -81,6 +89,7 @@ impl MemoryFile {
 			uri,
 			path,
 			bytes,
+			hash: content_hash(bytes),
 			caching: CachingBuilder::None,
 		}
 	}
@@ -95,20 +104,48 @@ impl MemoryFile {
 			uri,
 			path,
 			bytes,
+			hash: content_hash(bytes),
 			caching: CachingBuilder::MaxAge(max_age),
 		}
 	}
+
+	/// Used by `memory_file!` which computes the hash in a separate const.
+	#[doc(hidden)]
+	pub const fn new_with_hash(
+		uri: &'static str,
+		path: &'static str,
+		bytes: &'static [u8],
+		hash: u64,
+		max_age: Option<Duration>,
+	) -> Self {
+		Self {
+			uri,
+			path,
+			bytes,
+			hash,
+			caching: match max_age {
+				Some(max_age) => CachingBuilder::MaxAge(max_age),
+				None => CachingBuilder::Default,
+			},
+		}
+	}
 }
 
 impl IntoRoute for MemoryFile {
 	type IntoRoute = MemoryFileRoute;
 
 	fn into_route(self) -> MemoryFileRoute {
+		let etag = Etag::from_hash(self.bytes.len() as u64, self.hash);
+		let caching: Option<Caching> = self.caching.into();
+
 		MemoryFileRoute {
 			uri: self.uri,
 			path: self.path,
 			bytes: self.bytes,
-			caching: self.caching.into(),
+			caching: caching.map(|mut c| {
+				c.set_etag(etag);
+				c
+			}),
 		}
 	}
 }
@@ -124,6 +161,10 @@ pub struct MemoryFileRoute {
 impl Route for MemoryFileRoute {
 	fn validate_requirements(&self, _params: &ParamsNames, _data: &Resources) {}
 
+	fn kind(&self) -> RouteKind {
+		RouteKind::StaticFiles
+	}
+
 	fn path(&self) -> RoutePath {
 		RoutePath {
 			method: Some(Method::GET),
@@ -1,3 +1,4 @@
+use crate::header::Mime;
 use crate::into::IntoResponse;
 use crate::Response;
 
@@ -13,10 +14,12 @@ mod file;
 pub use file::File;
 
 mod partial_file;
-pub use partial_file::{PartialFile, Range};
+pub use partial_file::{ByteRange, PartialFile, Range};
 
 mod caching;
-pub use caching::Caching;
+#[doc(hidden)]
+pub use caching::content_hash;
+pub use caching::{Caching, Etag, Precondition};
 
 mod static_files;
 pub use static_files::{
@@ -29,6 +32,8 @@ pub use memory_files::{serve_memory_file, MemoryFile};
 /// Static get handler which servers/returns a file which gets loaded into
 /// the binary at compile time.
 ///
+/// The Etag of the file gets computed at compile time as well.
+///
 /// ## Example
 /// ```
 /// use std::time::Duration;
@@ -48,36 +53,55 @@ pub use memory_files::{serve_memory_file, MemoryFile};
 /// ```
 #[macro_export]
 macro_rules! memory_file {
+	(@hash $path:expr) => {{
+		// hashing large files takes a while
+		#[allow(long_running_const_eval)]
+		const HASH: u64 = $crate::fs::content_hash(include_bytes!($path));
+		HASH
+	}};
 	($uri:expr, $path:expr) => {
-		$crate::fs::MemoryFile::new($uri, $path, include_bytes!($path))
+		$crate::fs::MemoryFile::new_with_hash(
+			$uri,
+			$path,
+			include_bytes!($path),
+			$crate::memory_file!(@hash $path),
+			None,
+		)
 	};
 	($uri:expr, $path:expr, $duration:expr) => {
-		$crate::fs::MemoryFile::cache_with_age(
+		$crate::fs::MemoryFile::new_with_hash(
 			$uri,
 			$path,
 			include_bytes!($path),
-			$duration,
+			$crate::memory_file!(@hash $path),
+			Some($duration),
 		)
 	};
 }
 
 /// returns io::Error not found if the path is a directory
-pub(crate) async fn with_file<P>(path: P) -> io::Result<Response>
+pub(crate) async fn with_file<P>(
+	path: P,
+	mime_type: Mime,
+) -> io::Result<Response>
 where
 	P: AsRef<Path>,
 {
-	File::open(path).await.map(|f| f.into_response())
+	File::open_with_mime(path, mime_type)
+		.await
+		.map(|f| f.into_response())
 }
 
 /// returns io::Error not found if the path is a directory
 pub(crate) async fn with_partial_file<P>(
 	path: P,
+	mime_type: Mime,
 	range: Range,
 ) -> io::Result<Response>
 where
 	P: AsRef<Path>,
 {
-	PartialFile::open(path, range)
+	PartialFile::open_with_mime(path, mime_type, range)
 		.await
 		.map(|pf| pf.into_response())
 }
@@ -1,48 +1,244 @@
 use crate::header::{
-	Mime, RequestHeader, StatusCode, ACCEPT_RANGES, CONTENT_LENGTH,
-	CONTENT_RANGE, RANGE,
+	ContentType, Mime, RequestHeader, StatusCode, ACCEPT_RANGES,
+	CONTENT_LENGTH, CONTENT_RANGE, RANGE,
 };
 use crate::into::IntoResponse;
+use crate::response::ResponseBuilder;
 use crate::{Body, Response};
 
-use std::fmt;
+use std::collections::VecDeque;
 use std::io::SeekFrom;
+use std::ops::RangeInclusive;
 use std::path::Path;
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::task::{ready, Context, Poll};
 
-use io::AsyncSeekExt;
+use io::{AsyncRead, AsyncSeek, AsyncSeekExt};
 use tokio::{fs, io};
 
 use bytes::Bytes;
 
-#[derive(Debug, Clone)]
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+
+/// More ranges than this are ignored and the entire file is served.
+const MAX_RANGES: usize = 16;
+
+/// A single range of the `Range` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+	/// `start-end` or `start-`, zero indexed and inclusive
+	FromTo { start: usize, end: Option<usize> },
+	/// `-len`, the last `len` bytes
+	Suffix(usize),
+}
+
+impl ByteRange {
+	fn parse(s: &str) -> Option<Self> {
+		let (start, end) = s.split_once('-')?;
+
+		if start.is_empty() {
+			return parse_int(end).map(Self::Suffix);
+		}
+
+		let start = parse_int(start)?;
+		let end = match end {
+			"" => None,
+			end => Some(parse_int(end)?),
+		};
+
+		// the range is invalid and not just unsatisfiable
+		if end.is_some_and(|end| end < start) {
+			return None;
+		}
+
+		Some(Self::FromTo { start, end })
+	}
+
+	/// Returns the bytes this range selects in a file of `size` bytes or
+	/// `None` if the range cannot be satisfied.
+	pub fn resolve(&self, size: usize) -> Option<RangeInclusive<usize>> {
+		match *self {
+			Self::FromTo { start, .. } if start >= size => None,
+			Self::FromTo { start, end } => {
+				let end = end.map_or(size - 1, |end| end.min(size - 1));
+				Some(start..=end)
+			}
+			Self::Suffix(0) => None,
+			Self::Suffix(_) if size == 0 => None,
+			Self::Suffix(len) => Some(size - len.min(size)..=size - 1),
+		}
+	}
+}
+
+/// The `Range` header of a request, see
+/// [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#name-range).
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Range {
-	/// zero index and inclusive
-	pub start: usize,
-	/// zero index and inclusive
-	pub end: Option<usize>,
+	ranges: Vec<ByteRange>,
 }
 
 impl Range {
+	/// Returns `None` if the header is missing, invalid, does not use the
+	/// `bytes` unit or contains too many ranges, in which case the header
+	/// should be ignored.
 	pub fn parse(header: &RequestHeader) -> Option<Self> {
-		let range = header.value(RANGE)?;
-		if !range.starts_with("bytes=") {
+		let (unit, set) = header.value(RANGE)?.split_once('=')?;
+		if !unit.trim().eq_ignore_ascii_case("bytes") {
+			return None;
+		}
+
+		let ranges = set
+			.split(',')
+			.map(str::trim)
+			.filter(|r| !r.is_empty())
+			.map(ByteRange::parse)
+			.collect::<Option<Vec<_>>>()?;
+
+		if ranges.is_empty() || ranges.len() > MAX_RANGES {
 			return None;
 		}
 
-		let mut range = range[6..].split('-');
+		Some(Self { ranges })
+	}
 
-		let start: usize = range.next()?.parse().ok()?;
+	/// Creates a range from `start` to `end`, zero indexed and inclusive, if
+	/// `end` is `None` the range goes until the end of the file.
+	pub fn single(start: usize, end: Option<usize>) -> Self {
+		ByteRange::FromTo { start, end }.into()
+	}
 
-		let end = range.next()?;
-		let end: Option<usize> = if end != "" {
-			Some(end.parse().ok()?)
-		} else {
-			None
-		};
+	/// Returns the start and end if the header contains only a single range
+	/// which is not a suffix.
+	pub fn as_single(&self) -> Option<(usize, Option<usize>)> {
+		match self.ranges.as_slice() {
+			[ByteRange::FromTo { start, end }] => Some((*start, *end)),
+			_ => None,
+		}
+	}
+
+	pub fn ranges(&self) -> &[ByteRange] {
+		&self.ranges
+	}
+
+	/// Returns the satisfiable ranges for a file of `size` bytes in the
+	/// requested order.
+	///
+	/// If the list is empty the response should be
+	/// `416 Range Not Satisfiable`.
+	pub fn resolve(&self, size: usize) -> Vec<RangeInclusive<usize>> {
+		self.ranges.iter().filter_map(|r| r.resolve(size)).collect()
+	}
+}
+
+impl From<ByteRange> for Range {
+	fn from(range: ByteRange) -> Self {
+		Self {
+			ranges: vec![range],
+		}
+	}
+}
+
+/// Parses only digits, a value which does not fit is saturated.
+fn parse_int(s: &str) -> Option<usize> {
+	if s.is_empty() {
+		return None;
+	}
+
+	s.bytes().try_fold(0usize, |num, b| {
+		b.is_ascii_digit()
+			.then(|| num.saturating_mul(10).saturating_add((b - b'0') as usize))
+	})
+}
+
+fn range_len(range: &RangeInclusive<usize>) -> usize {
+	(range.end() + 1) - range.start()
+}
+
+fn not_satisfiable(size: usize) -> Response {
+	Response::builder()
+		.status_code(StatusCode::RANGE_NOT_SATISFIABLE)
+		.header(ACCEPT_RANGES, "bytes")
+		.header(CONTENT_RANGE, format!("bytes */{size}"))
+		.build()
+}
+
+fn single_part(
+	mime_type: Mime,
+	size: usize,
+	range: &RangeInclusive<usize>,
+) -> ResponseBuilder {
+	Response::builder()
+		.status_code(StatusCode::PARTIAL_CONTENT)
+		.content_type(mime_type)
+		.header(ACCEPT_RANGES, "bytes")
+		.header(CONTENT_LENGTH, range_len(range))
+		.header(
+			CONTENT_RANGE,
+			format!("bytes {}-{}/{size}", range.start(), range.end()),
+		)
+}
 
-		Some(Self { start, end })
+/// The framing of a `multipart/byteranges` body.
+struct Multipart {
+	boundary: String,
+	// written before the bytes of each range
+	heads: Vec<String>,
+	tail: String,
+	// the size of the entire body
+	len: usize,
+}
+
+impl Multipart {
+	fn new(
+		mime_type: Mime,
+		size: usize,
+		ranges: &[RangeInclusive<usize>],
+	) -> Self {
+		let boundary: String = thread_rng()
+			.sample_iter(&Alphanumeric)
+			.map(char::from)
+			.take(30)
+			.collect();
+		let content_type = ContentType::from(mime_type);
+
+		let heads: Vec<_> = ranges
+			.iter()
+			.enumerate()
+			.map(|(i, range)| {
+				format!(
+					"{}--{boundary}\r\n\
+					Content-Type: {content_type}\r\n\
+					Content-Range: bytes {}-{}/{size}\r\n\r\n",
+					if i == 0 { "" } else { "\r\n" },
+					range.start(),
+					range.end()
+				)
+			})
+			.collect();
+		let tail = format!("\r\n--{boundary}--\r\n");
+
+		let len = heads.iter().map(String::len).sum::<usize>()
+			+ ranges.iter().map(range_len).sum::<usize>()
+			+ tail.len();
+
+		Self {
+			boundary,
+			heads,
+			tail,
+			len,
+		}
+	}
+
+	fn response(&self) -> ResponseBuilder {
+		Response::builder()
+			.status_code(StatusCode::PARTIAL_CONTENT)
+			.content_type(format!(
+				"multipart/byteranges; boundary={}",
+				self.boundary
+			))
+			.header(ACCEPT_RANGES, "bytes")
+			.header(CONTENT_LENGTH, self.len)
 	}
 }
 
@@ -58,39 +254,42 @@ pub fn serve_memory_partial_file(
 		.unwrap_or(Mime::BINARY);
 
 	let size = bytes.len();
-	let start = range.start;
-	let end = range.end.unwrap_or(size - 1);
-
-	if end >= size || start >= end {
-		return Err(io::Error::new(
-			io::ErrorKind::Other,
-			RangeIncorrect(range),
-		));
-	}
-
-	let len = (end + 1) - start;
+	let ranges = range.resolve(size);
+
+	let response = match ranges.as_slice() {
+		[] => not_satisfiable(size),
+		[range] => single_part(mime_type, size, range)
+			.body(Bytes::from_static(&bytes[range.clone()]))
+			.build(),
+		ranges => {
+			let multipart = Multipart::new(mime_type, size, ranges);
+
+			let mut body = Vec::with_capacity(multipart.len);
+			for (head, range) in multipart.heads.iter().zip(ranges) {
+				body.extend_from_slice(head.as_bytes());
+				body.extend_from_slice(&bytes[range.clone()]);
+			}
+			body.extend_from_slice(multipart.tail.as_bytes());
 
-	let response = Response::builder()
-		.status_code(StatusCode::PARTIAL_CONTENT)
-		.content_type(mime_type)
-		.header(ACCEPT_RANGES, "bytes")
-		.header(CONTENT_LENGTH, len)
-		.header(CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, size))
-		.body(Bytes::from_static(&bytes[start..=end]))
-		.build();
+			multipart.response().body(body).build()
+		}
+	};
 
 	Ok(response)
 }
 
+/// A file which streams the requested ranges.
+///
+/// If none of the ranges can be satisfied the response is
+/// `416 Range Not Satisfiable`, multiple ranges are sent as
+/// `multipart/byteranges`.
 pub struct PartialFile {
 	file: fs::File,
 	mime_type: Mime,
 	// the size in bytes of the entire file
 	size: usize,
-	// where to start reading
-	start: usize,
-	// at which byte to stop reading (inclusive)
-	end: usize,
+	// the satisfiable ranges
+	ranges: Vec<RangeInclusive<usize>>,
 }
 
 impl PartialFile {
@@ -105,6 +304,19 @@ impl PartialFile {
 			.and_then(Mime::from_extension)
 			.unwrap_or(Mime::BINARY);
 
+		Self::open_with_mime(path, mime_type, range).await
+	}
+
+	/// Like `open` but the `Mime` is not derived from the extension, for
+	/// example for precompressed files.
+	pub(super) async fn open_with_mime<P>(
+		path: P,
+		mime_type: Mime,
+		range: Range,
+	) -> io::Result<Self>
+	where
+		P: AsRef<Path>,
+	{
 		let mut file = fs::File::open(path).await?;
 		let metadata = file.metadata().await?;
 
@@ -119,18 +331,13 @@ impl PartialFile {
 		let size: usize = metadata.len().try_into().map_err(|_| {
 			io::Error::new(io::ErrorKind::NotFound, "file to large")
 		})?;
-		let start = range.start;
-		let end = range.end.unwrap_or(size - 1);
+		let ranges = range.resolve(size);
 
-		if end >= size || start >= end {
-			return Err(io::Error::new(
-				io::ErrorKind::Other,
-				RangeIncorrect(range),
-			));
+		// multiple ranges seek while streaming
+		if let [range] = ranges.as_slice() {
+			file.seek(SeekFrom::Start(*range.start() as u64)).await?;
 		}
 
-		file.seek(SeekFrom::Start(start as u64)).await?;
-
 		// apache no-gzip
 		// content type
 
@@ -138,23 +345,44 @@ impl PartialFile {
 			file,
 			mime_type,
 			size,
-			start,
-			end,
+			ranges,
 		})
 	}
 }
 
-#[derive(Debug)]
-#[allow(dead_code)]
-pub struct RangeIncorrect(pub Range);
+/// Reads at most `max` bytes and returns how many bytes were read.
+fn poll_read_max(
+	file: Pin<&mut fs::File>,
+	cx: &mut Context,
+	buf: &mut io::ReadBuf,
+	max: u64,
+) -> Poll<io::Result<usize>> {
+	// take a max amount of buffer to not write to much
+	let (initialized, filled) = {
+		let mut buf = buf.take(max.try_into().unwrap_or(usize::MAX));
+		debug_assert!(buf.filled().is_empty());
+
+		ready!(file.poll_read(cx, &mut buf))?;
+
+		(buf.initialized().len(), buf.filled().len())
+	};
+
+	// this is safe since take returns a ReadBuf and it only returns
+	// bytes initializes that are.
+	unsafe {
+		buf.assume_init(buf.filled().len() + initialized);
+	}
+	buf.advance(filled);
 
-impl fmt::Display for RangeIncorrect {
-	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		fmt::Debug::fmt(self, f)
+	if filled == 0 {
+		return Poll::Ready(Err(io::Error::new(
+			io::ErrorKind::UnexpectedEof,
+			"The file is to short",
+		)));
 	}
-}
 
-impl std::error::Error for RangeIncorrect {}
+	Poll::Ready(Ok(filled))
+}
 
 // TODO NEED TO CHANGE u64
 // A File which streams a range
@@ -179,70 +407,139 @@ impl io::AsyncRead for FixedFile {
 		cx: &mut Context,
 		buf: &mut io::ReadBuf,
 	) -> Poll<io::Result<()>> {
+		let this = &mut *self;
+
 		// if finished reading
-		if self.remaining == 0 {
+		if this.remaining == 0 {
 			return Poll::Ready(Ok(()));
 		}
 
-		// take a max amount of buffer to not write to much
-		let (initialized, filled) = {
-			let mut buf =
-				buf.take(self.remaining.try_into().unwrap_or(usize::MAX));
-			debug_assert!(buf.filled().is_empty());
-
-			let res = Pin::new(&mut self.file).poll_read(cx, &mut buf);
-			match res {
-				Poll::Ready(Ok(())) => {}
-				Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
-				Poll::Pending => return Poll::Pending,
-			}
+		let filled = ready!(poll_read_max(
+			Pin::new(&mut this.file),
+			cx,
+			buf,
+			this.remaining
+		))?;
+		this.remaining -= filled as u64;
 
-			(buf.initialized().len(), buf.filled().len())
-		};
-
-		// this is safe since take returns a ReadBuf and it only returns
-		// bytes initializes that are.
-		unsafe {
-			buf.assume_init(buf.filled().len() + initialized);
-		}
-		buf.advance(filled);
+		Poll::Ready(Ok(()))
+	}
+}
 
-		if filled == 0 {
-			return Poll::Ready(Err(io::Error::new(
-				io::ErrorKind::UnexpectedEof,
-				"The file is to short",
-			)));
-		}
+#[derive(Debug)]
+enum Segment {
+	Bytes(Bytes),
+	Seek(u64),
+	Read(u64),
+}
 
-		self.remaining = self.remaining.checked_sub(filled as u64).unwrap();
+// A File which streams a multipart/byteranges body
+#[derive(Debug)]
+struct MultipartFile {
+	file: fs::File,
+	segments: VecDeque<Segment>,
+	// if a seek was started but did not complete yet
+	seeking: bool,
+}
 
-		Poll::Ready(Ok(()))
+impl io::AsyncRead for MultipartFile {
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context,
+		buf: &mut io::ReadBuf,
+	) -> Poll<io::Result<()>> {
+		let this = &mut *self;
+
+		loop {
+			let Some(segment) = this.segments.front_mut() else {
+				return Poll::Ready(Ok(()));
+			};
+
+			match segment {
+				Segment::Bytes(bytes) => {
+					let len = bytes.len().min(buf.remaining());
+					buf.put_slice(&bytes.split_to(len));
+
+					if bytes.is_empty() {
+						this.segments.pop_front();
+					}
+
+					return Poll::Ready(Ok(()));
+				}
+				Segment::Seek(pos) => {
+					let mut file = Pin::new(&mut this.file);
+					if !this.seeking {
+						file.as_mut().start_seek(SeekFrom::Start(*pos))?;
+						this.seeking = true;
+					}
+
+					ready!(file.poll_complete(cx))?;
+					this.seeking = false;
+					this.segments.pop_front();
+				}
+				Segment::Read(remaining) => {
+					let filled = ready!(poll_read_max(
+						Pin::new(&mut this.file),
+						cx,
+						buf,
+						*remaining
+					))?;
+					*remaining -= filled as u64;
+
+					if *remaining == 0 {
+						this.segments.pop_front();
+					}
+
+					return Poll::Ready(Ok(()));
+				}
+			}
+		}
 	}
 }
 
 impl IntoResponse for PartialFile {
 	fn into_response(self) -> Response {
-		let len = (self.end + 1) - self.start;
+		let range = match self.ranges.as_slice() {
+			[] => return not_satisfiable(self.size),
+			[range] => range.clone(),
+			ranges => {
+				let multipart =
+					Multipart::new(self.mime_type, self.size, ranges);
+
+				let mut segments = VecDeque::new();
+				for (head, range) in multipart.heads.iter().zip(ranges) {
+					segments.push_back(Segment::Bytes(head.clone().into()));
+					segments.push_back(Segment::Seek(*range.start() as u64));
+					segments.push_back(Segment::Read(range_len(range) as u64));
+				}
+				segments
+					.push_back(Segment::Bytes(multipart.tail.clone().into()));
+
+				let file = MultipartFile {
+					file: self.file,
+					segments,
+					seeking: false,
+				};
+
+				return multipart
+					.response()
+					.body(Body::from_async_reader(file))
+					.build();
+			}
+		};
 
-		let response = Response::builder()
-			.status_code(StatusCode::PARTIAL_CONTENT)
-			.content_type(self.mime_type)
-			.header(ACCEPT_RANGES, "bytes")
-			.header(CONTENT_LENGTH, len)
-			.header(
-				CONTENT_RANGE,
-				format!("bytes {}-{}/{}", self.start, self.end, self.size),
-			);
+		let response = single_part(self.mime_type, self.size, &range);
 
 		// the file is already at the correct start
 		// since open did that
 
-		// if self.end points to the end of the file just return the file
+		// if the range ends at the end of the file just return the file
 		// without limiting the reading
-		if self.end + 1 == self.size {
+		if range.end() + 1 == self.size {
 			response.body(Body::from_async_reader(self.file)).build()
 		} else {
-			let fixed_file = FixedFile::new(self.file, len as u64);
+			let fixed_file =
+				FixedFile::new(self.file, range_len(&range) as u64);
 			response.body(Body::from_async_reader(fixed_file)).build()
 		}
 	}
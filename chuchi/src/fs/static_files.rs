@@ -1,41 +1,105 @@
+use super::caching::{file_etag, precondition_response, use_range};
 use super::{with_file, with_partial_file};
 use super::{Caching, IntoPathBuf, Range};
 
 use crate::error::ClientErrorKind;
-use crate::header::{Method, StatusCode};
+use crate::header::{
+	Method, Mime, RequestHeader, StatusCode, ACCEPT_ENCODING, CACHE_CONTROL,
+	CONTENT_ENCODING,
+};
 use crate::into::{IntoResponse, IntoRoute};
-use crate::routes::{ParamsNames, PathParams, Route, RoutePath};
-use crate::util::PinnedFuture;
+use crate::routes::{ParamsNames, PathParams, Route, RouteKind, RoutePath};
+use crate::util::{add_vary, parse_quality, PinnedFuture};
 use crate::{Error, Request, Resources, Response};
 
 use std::borrow::Cow;
+use std::fs::Metadata;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 /// returns io::Error not found if the path is a directory
+///
+/// If caching is enabled the Etag gets derived from the content of the file
+/// and `Last-Modified` from its modification time.
+///
+/// If the client accepts it, a precompressed sibling like `app.js.br` or
+/// `app.js.gz` is served instead, with the `Mime` of the original file and a
+/// `Content-Encoding` header.
 pub async fn serve_file(
 	path: impl AsRef<Path>,
 	req: &Request,
 	caching: Option<Caching>,
 ) -> io::Result<Response> {
-	// check caching and if the etag matches return NOT_MODIFIED
-	if matches!(&caching, Some(c) if c.if_none_match(req.header())) {
-		return Ok(caching.unwrap().into_response());
+	let path = path.as_ref();
+	let header = req.header();
+
+	let metadata = tokio::fs::metadata(path).await?;
+
+	// make sure we open a file
+	if !metadata.is_file() {
+		return Err(io::Error::new(
+			io::ErrorKind::NotFound,
+			"expected file found folder",
+		));
+	}
+
+	let mime_type = path
+		.extension()
+		.and_then(|f| f.to_str())
+		.and_then(Mime::from_extension)
+		.unwrap_or(Mime::BINARY);
+
+	let (precompressed, vary) = find_precompressed(path, header).await;
+	let (path, metadata) = match &precompressed {
+		Some(p) => (p.path.as_path(), &p.metadata),
+		None => (path, &metadata),
+	};
+
+	let caching = match caching {
+		Some(caching) => Some(file_caching(path, metadata, caching).await?),
+		None => None,
+	};
+
+	// check the conditional headers, for example if the etag matches return
+	// NOT_MODIFIED
+	if let Some(mut res) = precondition_response(caching.as_ref(), header) {
+		if vary {
+			add_vary(&mut res.header.values, "accept-encoding");
+		}
+		return Ok(res);
 	}
 
-	let range = Range::parse(req.header());
+	let range =
+		Range::parse(header).filter(|_| use_range(caching.as_ref(), header));
 
 	let mut res = match range {
-		Some(range) => with_partial_file(path, range).await?.into_response(),
-		None => with_file(path).await?.into_response(),
+		Some(range) => with_partial_file(path, mime_type, range)
+			.await?
+			.into_response(),
+		None => with_file(path, mime_type).await?.into_response(),
 	};
 
+	let status_code = res.header.status_code;
+	let values = &mut res.header.values;
+
+	if let Some(p) = &precompressed {
+		if matches!(status_code, StatusCode::OK | StatusCode::PARTIAL_CONTENT) {
+			values.insert(CONTENT_ENCODING, p.encoding);
+		}
+	}
+
+	if vary {
+		add_vary(values, "accept-encoding");
+	}
+
 	// set etag
 	if let Some(caching) = caching {
 		if matches!(
-			res.header.status_code,
-			StatusCode::OK | StatusCode::NOT_FOUND
+			status_code,
+			StatusCode::OK
+				| StatusCode::PARTIAL_CONTENT
+				| StatusCode::NOT_FOUND
 		) {
 			caching.complete_header(&mut res.header);
 		}
@@ -44,6 +108,99 @@ pub async fn serve_file(
 	Ok(res)
 }
 
+/// Sets the etag and the last modification time of the file.
+async fn file_caching(
+	path: &Path,
+	metadata: &Metadata,
+	mut caching: Caching,
+) -> io::Result<Caching> {
+	caching.set_etag(file_etag(path, metadata).await?);
+	if let Ok(modified) = metadata.modified() {
+		caching.set_last_modified(modified);
+	}
+
+	Ok(caching)
+}
+
+/// The encodings of precompressed siblings and their extension, sorted by
+/// preference.
+const PRECOMPRESSED: [(&str, &str); 2] = [("br", "br"), ("gzip", "gz")];
+
+/// A precompressed sibling of a file, for example `app.js.br`.
+struct Precompressed {
+	path: PathBuf,
+	metadata: Metadata,
+	encoding: &'static str,
+}
+
+/// Returns the precompressed sibling the client prefers and if the file has
+/// any precompressed sibling, in which case the response varies with the
+/// `Accept-Encoding` header.
+async fn find_precompressed(
+	path: &Path,
+	header: &RequestHeader,
+) -> (Option<Precompressed>, bool) {
+	let accepted: Vec<(&str, f32)> = header
+		.value(ACCEPT_ENCODING)
+		.unwrap_or("")
+		.split(',')
+		.filter_map(|item| parse_quality(item.trim()))
+		.collect();
+
+	let quality = |token: &str| {
+		accepted
+			.iter()
+			.find(|(t, _)| t.eq_ignore_ascii_case(token))
+			.or_else(|| accepted.iter().find(|(t, _)| *t == "*"))
+			.map_or(0.0, |(_, q)| *q)
+	};
+
+	// the identity is only preferred if the client asks for it
+	let identity = accepted
+		.iter()
+		.find(|(t, _)| t.eq_ignore_ascii_case("identity"))
+		.map_or(0.0, |(_, q)| *q);
+
+	// the parts of a multipart/byteranges body cannot be encoded
+	let multi_range =
+		Range::parse(header).is_some_and(|r| r.ranges().len() > 1);
+
+	let mut preferred: Option<(Precompressed, f32)> = None;
+	let mut has_sibling = false;
+
+	for (encoding, extension) in PRECOMPRESSED {
+		let mut sibling = path.as_os_str().to_owned();
+		sibling.push(".");
+		sibling.push(extension);
+
+		let Ok(metadata) = tokio::fs::metadata(&sibling).await else {
+			continue;
+		};
+		if !metadata.is_file() {
+			continue;
+		}
+
+		has_sibling = true;
+
+		let q = quality(encoding);
+		if multi_range || q <= 0.0 || q < identity {
+			continue;
+		}
+
+		// keep the preference of the server if the q-values are equal
+		if preferred.as_ref().map_or(true, |(_, pref_q)| q > *pref_q) {
+			let sibling = Precompressed {
+				path: sibling.into(),
+				metadata,
+				encoding,
+			};
+			preferred = Some((sibling, q));
+		}
+	}
+
+	(preferred.map(|(p, _)| p), has_sibling)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum CachingBuilder {
 	None,
@@ -80,6 +237,7 @@ pub struct StaticFiles {
 	uri: &'static str,
 	path: &'static str,
 	caching: CachingBuilder,
+	index: Option<&'static str>,
 }
 
 impl StaticFiles {
@@ -89,6 +247,7 @@ impl StaticFiles {
 			uri,
 			path,
 			caching: CachingBuilder::Default,
+			index: None,
 		}
 	}
 
@@ -97,6 +256,7 @@ impl StaticFiles {
 			uri,
 			path,
 			caching: CachingBuilder::None,
+			index: None,
 		}
 	}
 
@@ -109,6 +269,36 @@ impl StaticFiles {
 			uri,
 			path,
 			caching: CachingBuilder::MaxAge(max_age),
+			index: None,
+		}
+	}
+
+	/// Creates a `StaticFiles` for a single page application.
+	///
+	/// Files which exist are served normally. If a path without an extension
+	/// like `/users/1` does not exist, `index` is served instead with a
+	/// `Cache-Control: no-cache` header, so the application can handle the
+	/// route. Missing assets like `/assets/missing.js` still return
+	/// `404 Not Found`.
+	///
+	/// `index` is relative to `path`.
+	///
+	/// ## Example
+	/// ```
+	/// use chuchi::fs::StaticFiles;
+	///
+	/// const APP: StaticFiles = StaticFiles::spa("/", "./dist", "index.html");
+	/// ```
+	pub const fn spa(
+		uri: &'static str,
+		path: &'static str,
+		index: &'static str,
+	) -> Self {
+		Self {
+			uri,
+			path,
+			caching: CachingBuilder::Default,
+			index: Some(index),
 		}
 	}
 }
@@ -118,9 +308,10 @@ impl IntoRoute for StaticFiles {
 
 	fn into_route(self) -> StaticFilesRoute {
 		StaticFilesRoute {
-			uri: self.uri.into(),
+			uri: self.uri.trim_end_matches('/').into(),
 			path: self.path.into(),
 			caching: self.caching.into(),
+			index: self.index.map(Into::into),
 		}
 	}
 }
@@ -144,6 +335,7 @@ pub struct StaticFilesOwned {
 	uri: String,
 	path: String,
 	caching: CachingBuilder,
+	index: Option<String>,
 }
 
 impl StaticFilesOwned {
@@ -153,6 +345,7 @@ impl StaticFilesOwned {
 			uri,
 			path,
 			caching: CachingBuilder::Default,
+			index: None,
 		}
 	}
 
@@ -161,6 +354,7 @@ impl StaticFilesOwned {
 			uri,
 			path,
 			caching: CachingBuilder::None,
+			index: None,
 		}
 	}
 
@@ -173,6 +367,18 @@ impl StaticFilesOwned {
 			uri,
 			path,
 			caching: CachingBuilder::MaxAge(max_age),
+			index: None,
+		}
+	}
+
+	/// Creates a `StaticFilesOwned` for a single page application, see
+	/// [`StaticFiles::spa`].
+	pub fn spa(uri: String, path: String, index: String) -> Self {
+		Self {
+			uri,
+			path,
+			caching: CachingBuilder::Default,
+			index: Some(index),
 		}
 	}
 }
@@ -185,6 +391,7 @@ impl IntoRoute for StaticFilesOwned {
 			uri: self.uri.trim_end_matches('/').to_string().into(),
 			path: self.path.into(),
 			caching: self.caching.into(),
+			index: self.index.map(Into::into),
 		}
 	}
 }
@@ -195,15 +402,27 @@ pub struct StaticFilesRoute {
 	uri: Cow<'static, str>,
 	path: Cow<'static, str>,
 	caching: Option<Caching>,
+	// relative to path, served if a route of a single page app is requested
+	index: Option<Cow<'static, str>>,
 }
 
 impl Route for StaticFilesRoute {
 	fn validate_requirements(&self, _params: &ParamsNames, _data: &Resources) {}
 
+	fn kind(&self) -> RouteKind {
+		RouteKind::StaticFiles
+	}
+
 	fn path(&self) -> RoutePath {
+		// a single page app also handles the uri itself
+		let path = match self.index {
+			Some(_) => format!("{}/{{*?rem}}", self.uri),
+			None => format!("{}/{{*rem}}", self.uri),
+		};
+
 		RoutePath {
 			method: Some(Method::GET),
-			path: format!("{}/{{*rem}}", self.uri).into(),
+			path: path.into(),
 		}
 	}
 
@@ -226,11 +445,29 @@ impl Route for StaticFilesRoute {
 				.map_err(|e| Error::new(ClientErrorKind::BadRequest, e))?;
 
 			// build full pathbuf
-			let path_buf = Path::new(&*self.path).join(path_buf);
-
-			serve_file(path_buf, &req, caching)
-				.await
-				.map_err(Error::from_client_io)
+			let full_path = Path::new(&*self.path).join(&path_buf);
+
+			let res = serve_file(full_path, req, caching.clone()).await;
+
+			match (res, &self.index) {
+				// only routes of the app fall back to the index, a missing
+				// asset is still not found
+				(Err(e), Some(index))
+					if e.kind() == io::ErrorKind::NotFound
+						&& path_buf.extension().is_none() =>
+				{
+					let index = Path::new(&*self.path).join(&**index);
+					let mut res = serve_file(index, req, caching)
+						.await
+						.map_err(Error::from_client_io)?;
+
+					// the index references the assets of the current build
+					res.header.values.insert(CACHE_CONTROL, "no-cache");
+
+					Ok(res)
+				}
+				(res, _) => res.map_err(Error::from_client_io),
+			}
 		})
 	}
 }
@@ -367,6 +604,10 @@ pub struct StaticFileRoute {
 impl Route for StaticFileRoute {
 	fn validate_requirements(&self, _params: &ParamsNames, _data: &Resources) {}
 
+	fn kind(&self) -> RouteKind {
+		RouteKind::StaticFiles
+	}
+
 	fn path(&self) -> RoutePath {
 		RoutePath {
 			method: Some(Method::GET),
@@ -1,7 +1,14 @@
-use crate::error::ServerErrorKind;
-use crate::header::Mime;
+use crate::error::{ClientErrorKind, ErrorKind, ServerErrorKind};
+use crate::extractor::{Extractor, ExtractorError};
+use crate::header::{Mime, CONTENT_TYPE};
+use crate::into::IntoResponse;
 use crate::{Body, Error, Response};
 
+use std::error::Error as StdError;
+use std::io;
+use std::ops::{Deref, DerefMut};
+
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 pub trait IntoRouteResult<T> {
@@ -40,3 +47,146 @@ where
 
 	Ok(resp)
 }
+
+/// Deserializes the body of the request as json or serializes the value as a
+/// json response.
+///
+/// As an extractor the `Content-Type` needs to be `application/json`,
+/// otherwise a `415 Unsupported Media Type` is returned. Invalid json returns
+/// a `400 Bad Request` and json which does not match the type a
+/// `422 Unprocessable Entity`. The size limit and timeout of the request
+/// apply.
+///
+/// ## Example
+/// ```
+/// use chuchi::json::Json;
+/// use chuchi::post;
+///
+/// #[derive(serde::Deserialize, serde::Serialize)]
+/// struct User {
+/// 	name: String,
+/// }
+///
+/// #[post("/users")]
+/// fn create_user(user: Json<User>) -> Json<User> {
+/// 	user
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+	pub fn into_inner(self) -> T {
+		self.0
+	}
+}
+
+impl<T> Deref for Json<T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T> DerefMut for Json<T> {
+	fn deref_mut(&mut self) -> &mut T {
+		&mut self.0
+	}
+}
+
+impl<'a, T, R> Extractor<'a, R> for Json<T>
+where
+	T: DeserializeOwned + Send + 'a,
+{
+	type Error = JsonError;
+	type Prepared = T;
+
+	extractor_validate!();
+
+	extractor_prepare!(|prepare| {
+		let content_type = prepare.header.value(CONTENT_TYPE);
+		// parameters like charset are ignored
+		let mime = content_type
+			.and_then(|ct| ct.split(';').next())
+			.and_then(|ct| ct.trim().parse::<Mime>().ok());
+
+		if mime != Some(Mime::JSON) {
+			return Err(JsonError::UnsupportedMediaType(
+				content_type.map(Into::into),
+			));
+		}
+
+		let mut prepare = prepare;
+		let body = prepare.take_body().ok_or(JsonError::NoBody)?;
+		let bytes = body.into_bytes().await.map_err(JsonError::Read)?;
+
+		let mut de = serde_json::Deserializer::from_slice(&bytes);
+		let value = serde_path_to_error::deserialize(&mut de).map_err(|e| {
+			JsonError::Json {
+				path: e.path().to_string(),
+				error: e.into_inner(),
+			}
+		})?;
+
+		// make sure there are no trailing characters
+		de.end().map_err(|error| JsonError::Json {
+			path: ".".into(),
+			error,
+		})?;
+
+		Ok(value)
+	});
+
+	extractor_extract!(|extract| { Ok(Json(extract.prepared)) });
+}
+
+impl<T> IntoResponse for Json<T>
+where
+	T: Serialize,
+{
+	fn into_response(self) -> Response {
+		serialize_to_response(&self.0).unwrap_or_else(|e| {
+			tracing::error!("failed to serialize json response: {e}");
+			e.status_code().into()
+		})
+	}
+}
+
+/// The body of the request could not be deserialized as json.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum JsonError {
+	#[error("Expected content-type application/json got {0:?}")]
+	UnsupportedMediaType(Option<String>),
+	#[error("The route does not provide a body")]
+	NoBody,
+	#[error("Failed to read the body: {0}")]
+	Read(io::Error),
+	#[error("Failed to deserialize json at {path}: {error}")]
+	Json {
+		/// The path of the field which failed, for example `user.name`.
+		path: String,
+		error: serde_json::Error,
+	},
+}
+
+impl ExtractorError for JsonError {
+	fn error_kind(&self) -> ErrorKind {
+		match self {
+			Self::UnsupportedMediaType(_) => {
+				ClientErrorKind::UnsupportedMediaType.into()
+			}
+			Self::NoBody => ServerErrorKind::InternalServerError.into(),
+			Self::Read(e) => ClientErrorKind::from_io(e).into(),
+			Self::Json { error, .. } if error.is_data() => {
+				ClientErrorKind::UnprocessableEntity.into()
+			}
+			Self::Json { .. } => ClientErrorKind::BadRequest.into(),
+		}
+	}
+
+	fn into_std(self) -> Box<dyn StdError + Send + Sync> {
+		Box::new(self)
+	}
+}
@@ -10,7 +10,9 @@ use resources::Resources;
 pub mod state;
 
 pub mod routes;
-use routes::{Catcher, ParamsNames, RawRoute, Route, Routes};
+use routes::{
+	Catcher, Middleware, ParamsNames, RawRoute, Route, Routes, Scope,
+};
 
 #[macro_use]
 pub mod util;
@@ -26,11 +28,14 @@ pub use extractor::Res;
 
 mod server;
 use server::Server;
+pub use server::{ChuchiTask, Shutdown};
 
 mod routing;
 use routing::{RequestConfigs, ServerShared};
 use tracing::info;
 
+pub mod cors;
+
 #[cfg(feature = "fs")]
 #[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
 pub mod fs;
@@ -51,6 +56,30 @@ pub mod graphql;
 #[cfg_attr(docsrs, doc(cfg(feature = "api")))]
 pub mod api;
 
+#[cfg(feature = "tls")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+pub mod tls;
+
+#[cfg(feature = "tower")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+pub mod tower;
+
+#[cfg(feature = "secure-cookies")]
+#[cfg_attr(docsrs, doc(cfg(feature = "secure-cookies")))]
+pub mod cookie;
+
+#[cfg(feature = "sessions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sessions")))]
+pub mod session;
+
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub mod compression;
+
+#[cfg(feature = "sse")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sse")))]
+pub mod sse;
+
 #[cfg(feature = "json")]
 #[doc(hidden)]
 pub use serde_json;
@@ -60,11 +89,13 @@ pub mod service {
 }
 
 use std::any::Any;
+use std::future::{self, Future};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::net::ToSocketAddrs;
+use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
 
 pub use chuchi_core::{
@@ -84,6 +115,9 @@ pub struct Chuchi {
 	resources: Resources,
 	routes: Routes,
 	configs: RequestConfigs,
+	shutdown_timeout: Option<Duration>,
+	#[cfg(feature = "tls")]
+	tls: Option<Arc<tls::ServerConfig>>,
 }
 
 impl Chuchi {
@@ -104,6 +138,9 @@ impl Chuchi {
 			resources: Resources::new(),
 			routes: Routes::new(),
 			configs: RequestConfigs::new(),
+			shutdown_timeout: None,
+			#[cfg(feature = "tls")]
+			tls: None,
 		}
 	}
 
@@ -125,7 +162,10 @@ impl Chuchi {
 		R: RawRoute + 'static,
 	{
 		let path = route.path();
-		let names = ParamsNames::parse(&path.path);
+		let mut names = ParamsNames::parse(&path.path);
+		if let Some(host) = route.host() {
+			names.extend_host(host);
+		}
 		route.validate_requirements(&names, &self.resources);
 		self.routes.push_raw(path, route)
 	}
@@ -137,7 +177,10 @@ impl Chuchi {
 	{
 		let route = route.into_route();
 		let path = route.path();
-		let names = ParamsNames::parse(&path.path);
+		let mut names = ParamsNames::parse(&path.path);
+		if let Some(host) = route.host() {
+			names.extend_host(host);
+		}
 		route.validate_requirements(&names, &self.resources);
 		self.routes.push(path, route)
 	}
@@ -151,6 +194,88 @@ impl Chuchi {
 		self.routes.push_catcher(catcher)
 	}
 
+	/// Adds a `Middleware` to the chuchi.
+	///
+	/// Middlewares are called in the order they were added, the first one
+	/// added is the outermost.
+	pub fn add_middleware<M>(&mut self, middleware: M)
+	where
+		M: Middleware + 'static,
+	{
+		middleware.validate_requirements(&self.resources);
+		self.routes.push_middleware(middleware)
+	}
+
+	/// Mounts every route, raw route, catcher and middleware of the `Scope`
+	/// under the given prefix.
+	///
+	/// The prefix is prepended to the path of every route, so a route `/users`
+	/// mounted at `/api` can be reached with `/api/users`.
+	pub fn mount(&mut self, prefix: &str, scope: Scope) {
+		let (routes, raw_routes, catchers) = scope.into_scoped(prefix);
+
+		for route in raw_routes {
+			self.add_raw_route(route);
+		}
+
+		for route in routes {
+			self.add_route(route);
+		}
+
+		for catcher in catchers {
+			self.add_catcher(catcher);
+		}
+	}
+
+	/// Builds the path of the route with the given name, filling in the
+	/// params.
+	///
+	/// ## Example
+	/// ```
+	/// use chuchi::extractor::PathStr;
+	/// use chuchi::get;
+	///
+	/// #[get("/users/{id}", name = "user")]
+	/// fn user(id: &PathStr) -> String {
+	/// 	format!("user {id}")
+	/// }
+	///
+	/// let mut server = chuchi::Chuchi::new_localhost();
+	/// server.add_route(user);
+	///
+	/// let url = server.url_for("user", &[("id", "1")]).unwrap();
+	/// assert_eq!(url, "/users/1");
+	/// ```
+	pub fn url_for(
+		&self,
+		name: &str,
+		params: &[(&str, &str)],
+	) -> std::result::Result<String, routes::UrlError> {
+		self.routes.url_for(name, params)
+	}
+
+	/// Returns information about every route in the order they were added.
+	///
+	/// ## Example
+	/// ```
+	/// use chuchi::get;
+	///
+	/// #[get("/users/{id}")]
+	/// fn user() -> &'static str {
+	/// 	"user"
+	/// }
+	///
+	/// let mut server = chuchi::Chuchi::new_localhost();
+	/// server.add_route(user);
+	///
+	/// for route in server.routes_info() {
+	/// 	println!("{:?} {} -> {}", route.method, route.path, route.type_name);
+	/// }
+	/// ```
+	pub fn routes_info(&self) -> &[routes::RouteInfo] {
+		self.routes.info()
+	}
+
 	/// Sets the request size limit. The default is 4 kilobytes.
 	///
 	/// This can be changed in every Route.
@@ -170,6 +295,25 @@ impl Chuchi {
 		self.configs.timeout(timeout)
 	}
 
+	/// Decompresses request bodies with a `Content-Encoding` of gzip,
+	/// deflate or br. The default is `false`.
+	///
+	/// The request size limit applies to the decompressed body. Requests
+	/// with an unsupported encoding are passed on unchanged.
+	#[cfg(feature = "decompression")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "decompression")))]
+	pub fn request_decompression(&mut self, enabled: bool) {
+		self.configs.decompress(enabled)
+	}
+
+	/// Sets how long open connections get to finish after a shutdown was
+	/// requested. The default is 30 seconds.
+	///
+	/// Connections which are still open after the timeout are closed.
+	pub fn shutdown_timeout(&mut self, timeout: Duration) {
+		self.shutdown_timeout = Some(timeout);
+	}
+
 	/// Updates the socket address that the server will bind to.
 	///
 	/// This can only be called before the server is built.
@@ -186,6 +330,34 @@ impl Chuchi {
 		Ok(())
 	}
 
+	/// Enables https, every connection will be terminated with tls.
+	///
+	/// Http2 is negotiated via alpn if the `http2` feature is enabled.
+	///
+	/// ## Errors
+	/// If the private key does not match the certificate or is not supported.
+	#[cfg(feature = "tls")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+	pub fn tls(
+		&mut self,
+		cert_chain: Vec<tls::CertificateDer<'static>>,
+		private_key: tls::PrivateKeyDer<'static>,
+	) -> Result<()> {
+		let config = tls::server_config(cert_chain, private_key)?;
+		self.tls = Some(Arc::new(config));
+		Ok(())
+	}
+
+	/// Enables https with a custom rustls `ServerConfig`.
+	///
+	/// ## Note
+	/// The alpn protocols need to be set manually.
+	#[cfg(feature = "tls")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+	pub fn tls_config(&mut self, config: Arc<tls::ServerConfig>) {
+		self.tls = Some(config);
+	}
+
 	/// Binds to the address and prepares to serve requests.
 	///
 	/// You need to call run on the `ChuchiServer` so that it starts handling
@@ -197,7 +369,16 @@ impl Chuchi {
 			self.configs,
 		));
 
-		let server = Server::bind(self.addr, wood.clone()).await?;
+		let mut server = Server::bind(self.addr, wood.clone()).await?;
+
+		if let Some(timeout) = self.shutdown_timeout {
+			server.set_shutdown_timeout(timeout);
+		}
+
+		#[cfg(feature = "tls")]
+		if let Some(tls) = self.tls {
+			server.set_tls(tls);
+		}
 
 		Ok(ChuchiServer {
 			shared: wood,
@@ -214,6 +395,17 @@ impl Chuchi {
 		server.run().await
 	}
 
+	/// Starts the chuchi server and stops it once the `signal` resolves.
+	///
+	/// See [`ChuchiServer::run_with_shutdown`].
+	pub async fn run_with_shutdown<F>(self, signal: F) -> Result<Shutdown>
+	where
+		F: Future<Output = ()>,
+	{
+		let server = self.build().await?;
+		server.run_with_shutdown(signal).await
+	}
+
 	/// Starts the chuchi server, and spawns it on a new tokio task.
 	///
 	/// ## Note
@@ -222,6 +414,25 @@ impl Chuchi {
 		tokio::spawn(async move { self.run().await.unwrap() })
 	}
 
+	/// Starts the chuchi server, and spawns it on a new tokio task which can
+	/// be stopped gracefully with [`ChuchiTask::shutdown`].
+	///
+	/// ## Note
+	/// Under normal conditions this task should run until it gets shut down.
+	pub fn run_task_with_shutdown(self) -> ChuchiTask {
+		let (tx, rx) = oneshot::channel();
+
+		let handle = tokio::spawn(async move {
+			self.run_with_shutdown(async {
+				let _ = rx.await;
+			})
+			.await
+			.unwrap()
+		});
+
+		ChuchiTask::new(handle, tx)
+	}
+
 	/// Creates a `ChuchiShared` without starting the server.
 	///
 	/// In most cases you should use `build` and then call `shared` on the `ChuchiServer`.
@@ -257,15 +468,31 @@ impl ChuchiServer {
 	}
 
 	pub async fn run(self) -> Result<()> {
+		self.run_with_shutdown(future::pending()).await.map(|_| ())
+	}
+
+	/// Runs the server until the `signal` resolves.
+	///
+	/// After that no new connections are accepted and open connections are
+	/// told to finish their current requests. Connections which are still
+	/// open after the shutdown timeout are closed.
+	///
+	/// ## Note
+	/// Upgraded connections, like websockets, are not tracked.
+	pub async fn run_with_shutdown<F>(self, signal: F) -> Result<Shutdown>
+	where
+		F: Future<Output = ()>,
+	{
 		info!("Running server on addr: {}", self.local_addr().unwrap());
 
 		#[cfg(any(feature = "http1", feature = "http2"))]
 		{
-			self.server.serve().await
+			self.server.serve_with_shutdown(signal).await
 		}
 
 		#[cfg(not(any(feature = "http1", feature = "http2")))]
 		{
+			let _ = signal;
 			panic!("http1 or http2 feature must be enabled")
 		}
 	}
@@ -287,7 +514,31 @@ impl ChuchiShared {
 		self.inner.data()
 	}
 
-	/// Routes the request to normal routes and returns their result.
+	/// Builds the path of the route with the given name, filling in the
+	/// params.
+	///
+	/// See [`Chuchi::url_for`].
+	pub fn url_for(
+		&self,
+		name: &str,
+		params: &[(&str, &str)],
+	) -> std::result::Result<String, routes::UrlError> {
+		self.inner.routes().url_for(name, params)
+	}
+
+	/// Returns information about every route in the order they were added.
+	///
+	/// See [`Chuchi::routes_info`].
+	pub fn routes_info(&self) -> &[routes::RouteInfo] {
+		self.inner.routes().info()
+	}
+
+	/// Routes the request through the middlewares to normal routes and
+	/// returns their result.
+	///
+	/// Requests are handled like by the server, including `405` and
+	/// `OPTIONS` responses, catchers and `HEAD` requests, only raw routes are
+	/// not called.
 	///
 	/// Useful for tests and niche applications.
 	///
@@ -0,0 +1,110 @@
+use crate::header::HOST;
+
+/// A pattern which is matched against the host of a request.
+///
+/// - `example.com` only matches `example.com`
+/// - `*.example.com` matches every subdomain of `example.com`
+/// - `{tenant}.example.com` matches a single subdomain and stores it in the
+///   param `tenant`
+///
+/// Hosts are compared case insensitive and without the port.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HostPattern {
+	// if true at least one more label needs to be in front of the labels
+	wildcard: bool,
+	labels: Vec<Label>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Label {
+	Exact(String),
+	Param(String),
+}
+
+impl HostPattern {
+	#[track_caller]
+	pub fn parse(pattern: &str) -> Self {
+		let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+		let (wildcard, rest) = match pattern.strip_prefix("*.") {
+			Some(rest) => (true, rest),
+			None => (false, pattern.as_str()),
+		};
+
+		let labels = rest
+			.split('.')
+			.map(|label| {
+				assert!(
+					!label.is_empty() && !label.contains('*'),
+					"invalid host pattern {pattern:?}"
+				);
+
+				match label.strip_prefix('{').and_then(|l| l.strip_suffix('}'))
+				{
+					Some(name) => Label::Param(name.to_string()),
+					None => {
+						assert!(
+							!label.contains(['{', '}']),
+							"a param needs to be a whole label in {pattern:?}"
+						);
+						Label::Exact(label.to_string())
+					}
+				}
+			})
+			.collect();
+
+		Self { wildcard, labels }
+	}
+
+	/// Returns the params if the host matches.
+	pub fn matches(&self, host: &str) -> Option<Vec<(String, String)>> {
+		let mut host = host.trim_end_matches('.').rsplit('.');
+		let mut params = vec![];
+
+		for label in self.labels.iter().rev() {
+			let part = host.next().filter(|p| !p.is_empty())?;
+
+			match label {
+				Label::Exact(l) if l.eq_ignore_ascii_case(part) => {}
+				Label::Exact(_) => return None,
+				Label::Param(name) => {
+					params.push((name.clone(), part.to_string()))
+				}
+			}
+		}
+
+		match (self.wildcard, host.next()) {
+			(false, None) => Some(params),
+			(true, Some(part)) if !part.is_empty() => Some(params),
+			_ => None,
+		}
+	}
+
+	/// Exact patterns are tried first, then patterns with params and at last
+	/// wildcards.
+	pub fn priority(&self) -> u8 {
+		if self.wildcard {
+			2
+		} else if self.labels.iter().any(|l| matches!(l, Label::Param(_))) {
+			1
+		} else {
+			0
+		}
+	}
+}
+
+/// Returns the host of a request without the port, either from the uri or
+/// from the `Host` header.
+pub(crate) fn request_host<B>(req: &hyper::Request<B>) -> Option<&str> {
+	if let Some(host) = req.uri().host() {
+		return Some(host);
+	}
+
+	let host = req.headers().get(HOST)?.to_str().ok()?;
+
+	// ipv6 addresses are in brackets
+	if let Some(end) = host.find(']') {
+		return Some(&host[..=end]);
+	}
+
+	Some(host.split(':').next().unwrap_or(host))
+}
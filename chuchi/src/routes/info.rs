@@ -0,0 +1,39 @@
+use crate::header::Method;
+
+/// What kind of route a `RouteInfo` describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RouteKind {
+	/// A `RawRoute`, for example a `TowerRoute`.
+	Raw,
+	/// A `Route`, for example created with `#[get(..)]`.
+	Basic,
+	/// A websocket route created with `#[ws(..)]`.
+	Ws,
+	/// An api route created with `#[api(..)]` or an api stream.
+	Api,
+	/// A static file, static directory or memory file.
+	StaticFiles,
+}
+
+/// Information about a registered route.
+///
+/// Routes are listed in the order they were added, which allows to print a
+/// route table at startup or to check in a test that a route exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RouteInfo {
+	/// `None` matches every method.
+	pub method: Option<Method>,
+	/// The path template, for example `/users/{id}`.
+	pub path: String,
+	/// The host pattern, if the route only matches a specific host.
+	pub host: Option<String>,
+	/// The params of the host and the path in the order they appear.
+	pub params: Vec<String>,
+	pub kind: RouteKind,
+	/// The name used with `Chuchi::url_for`.
+	pub name: Option<String>,
+	/// The rust type implementing the route.
+	pub type_name: &'static str,
+}
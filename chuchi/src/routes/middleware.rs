@@ -0,0 +1,206 @@
+use super::{PathParams, Route, Routes};
+
+use crate::header::{Method, RequestHeader, Uri};
+use crate::routing;
+use crate::util::PinnedFuture;
+use crate::{Request, Resources, Response};
+
+use std::any::TypeId;
+
+/// A `Middleware` wraps every `Route`.
+///
+/// It get's called after the `RawRoute`'s and before the `Route`, so it can
+/// modify the request, return a response early or change the response of the
+/// `Route`. Catchers are called after all middlewares finished.
+///
+/// Middlewares are called in the order they were added. A `Route` can opt out
+/// of a middleware with `Route::skip_middleware`.
+///
+/// ## Example
+/// ```
+/// use chuchi::routes::{Middleware, Next};
+/// use chuchi::resources::Resources;
+/// use chuchi::util::PinnedFuture;
+/// use chuchi::{Request, Response};
+///
+/// struct Timing;
+///
+/// impl Middleware for Timing {
+/// 	fn call<'a>(
+/// 		&'a self,
+/// 		req: &'a mut Request,
+/// 		_resources: &'a Resources,
+/// 		next: Next<'a>,
+/// 	) -> PinnedFuture<'a, chuchi::Result<Response>> {
+/// 		PinnedFuture::new(async move {
+/// 			let start = std::time::Instant::now();
+/// 			let resp = next.run(req).await;
+/// 			println!("took {:?}", start.elapsed());
+/// 			resp
+/// 		})
+/// 	}
+/// }
+/// ```
+pub trait Middleware: Send + Sync {
+	// check if every data you expect is in Data
+	fn validate_requirements(&self, _resources: &Resources) {}
+
+	fn call<'a>(
+		&'a self,
+		req: &'a mut Request,
+		resources: &'a Resources,
+		next: Next<'a>,
+	) -> PinnedFuture<'a, crate::Result<Response>>;
+}
+
+pub(crate) struct BoxedMiddleware {
+	type_id: TypeId,
+	inner: Box<dyn Middleware>,
+}
+
+impl BoxedMiddleware {
+	pub fn new<M>(middleware: M) -> Self
+	where
+		M: Middleware + 'static,
+	{
+		Self {
+			type_id: TypeId::of::<M>(),
+			inner: Box::new(middleware),
+		}
+	}
+
+	pub fn validate_requirements(&self, resources: &Resources) {
+		self.inner.validate_requirements(resources);
+	}
+}
+
+/// The remaining middlewares and the `Route` of a request.
+pub struct Next<'a> {
+	middlewares: &'a [BoxedMiddleware],
+	endpoint: Endpoint<'a>,
+	resources: &'a Resources,
+}
+
+enum Endpoint<'a> {
+	/// The route get's matched once and only matched again if a middleware
+	/// changes the method or the uri of the request.
+	Routes(&'a Routes, Option<Matched<'a>>),
+	/// The route was already matched, for example in a `Scope`.
+	Route(&'a dyn Route, &'a PathParams),
+}
+
+/// The result of matching a request against the routes.
+struct Matched<'a> {
+	method: Method,
+	uri: Uri,
+	route: Option<(&'a dyn Route, PathParams)>,
+}
+
+impl<'a> Next<'a> {
+	pub(crate) fn new(routes: &'a Routes, resources: &'a Resources) -> Self {
+		Self {
+			middlewares: routes.middlewares(),
+			endpoint: Endpoint::Routes(routes, None),
+			resources,
+		}
+	}
+
+	pub(crate) fn new_route(
+		middlewares: &'a [BoxedMiddleware],
+		route: &'a dyn Route,
+		params: &'a PathParams,
+		resources: &'a Resources,
+	) -> Self {
+		Self {
+			middlewares,
+			endpoint: Endpoint::Route(route, params),
+			resources,
+		}
+	}
+
+	/// Calls the next middleware or the `Route` if there are no middlewares
+	/// left.
+	///
+	/// If a middleware changes the method or the uri of the request, the
+	/// route gets matched again, so middlewares can rewrite it.
+	/// If no route matches a `404 Not Found` response is returned, or a
+	/// `405 Method Not Allowed` if the path matches a route with another
+	/// method. `OPTIONS` requests get answered automatically.
+	pub fn run<'b>(
+		mut self,
+		req: &'b mut Request,
+	) -> PinnedFuture<'b, crate::Result<Response>>
+	where
+		'a: 'b,
+	{
+		PinnedFuture::new(async move {
+			while let Some((middleware, rest)) = self.middlewares.split_first()
+			{
+				self.middlewares = rest;
+
+				if self.skips(middleware, req) {
+					continue;
+				}
+
+				let resources = self.resources;
+				return middleware.inner.call(req, resources, self).await;
+			}
+
+			let resources = self.resources;
+			match &mut self.endpoint {
+				Endpoint::Routes(routes, matched) => {
+					match match_route(routes, matched, req.header()) {
+						Some((route, params)) => {
+							route.call(req, params, resources).await
+						}
+						None => {
+							Ok(routing::no_route_response(routes, req.header()))
+						}
+					}
+				}
+				Endpoint::Route(route, params) => {
+					route.call(req, params, resources).await
+				}
+			}
+		})
+	}
+
+	fn skips(&mut self, middleware: &BoxedMiddleware, req: &Request) -> bool {
+		let route = match &mut self.endpoint {
+			Endpoint::Routes(routes, matched) => {
+				match_route(routes, matched, req.header()).map(|(r, _)| r)
+			}
+			Endpoint::Route(route, _) => Some(*route),
+		};
+
+		route.is_some_and(|route| route.skip_middleware(middleware.type_id))
+	}
+}
+
+/// Returns the route matching the request, reusing the previous result if
+/// the method and the uri did not change.
+fn match_route<'a, 'm>(
+	routes: &'a Routes,
+	matched: &'m mut Option<Matched<'a>>,
+	header: &RequestHeader,
+) -> Option<(&'a dyn Route, &'m PathParams)> {
+	let uri = header.uri();
+	let outdated = matched
+		.as_ref()
+		.map_or(true, |m| m.method != header.method || m.uri != *uri);
+
+	if outdated {
+		let route = routes
+			.route(&header.method, uri.host(), uri.path())
+			.map(|(route, params)| (&**route as &dyn Route, params));
+
+		*matched = Some(Matched {
+			method: header.method.clone(),
+			uri: uri.clone(),
+			route,
+		});
+	}
+
+	let (route, params) = matched.as_ref()?.route.as_ref()?;
+	Some((*route, params))
+}
@@ -10,11 +10,29 @@ use router::Router;
 mod catcher;
 pub use catcher::Catcher;
 
+mod middleware;
+use middleware::BoxedMiddleware;
+pub use middleware::{Middleware, Next};
+
+mod scope;
+pub use scope::Scope;
+
+mod url;
+pub use url::{fill_path, UrlError};
+
+mod host;
+pub(crate) use host::request_host;
+use host::HostPattern;
+
+mod info;
+pub use info::{RouteInfo, RouteKind};
+
 mod path_params;
 pub use path_params::{ParamsNames, PathParams};
 
 use crate::header::Method;
 
+use std::collections::HashMap;
 use std::slice;
 
 type BoxedRawRoute = Box<dyn RawRoute>;
@@ -22,26 +40,117 @@ type BoxedRoute = Box<dyn Route>;
 type BoxedCatcher = Box<dyn Catcher>;
 
 pub struct Routes {
+	// ordered by priority, the routes without a host are always last
+	hosts: Vec<HostRoutes>,
+	catcher: Vec<BoxedCatcher>,
+	middleware: Vec<BoxedMiddleware>,
+	// name -> path template
+	names: HashMap<String, String>,
+	// in the order the routes were added
+	info: Vec<RouteInfo>,
+}
+
+/// All routes which match a host pattern.
+struct HostRoutes {
+	// None matches every host
+	pattern: Option<HostPattern>,
 	raw: Router<BoxedRawRoute>,
 	basic: Router<BoxedRoute>,
-	catcher: Vec<BoxedCatcher>,
 }
 
-impl Routes {
-	pub fn new() -> Self {
+impl HostRoutes {
+	fn new(pattern: Option<HostPattern>) -> Self {
 		Self {
+			pattern,
 			raw: Router::new(),
 			basic: Router::new(),
+		}
+	}
+
+	/// Returns the params of the host if it matches.
+	fn matches(&self, host: Option<&str>) -> Option<Vec<(String, String)>> {
+		match (&self.pattern, host) {
+			(None, _) => Some(vec![]),
+			(Some(pattern), Some(host)) => pattern.matches(host),
+			(Some(_), None) => None,
+		}
+	}
+
+	fn priority(&self) -> u8 {
+		self.pattern
+			.as_ref()
+			.map(HostPattern::priority)
+			.unwrap_or(u8::MAX)
+	}
+
+	/// `HEAD` requests can be answered by `GET` routes
+	fn head_fallback<'a, 'b>(
+		&'a self,
+		method: &Method,
+		path: &'b str,
+	) -> Option<(&'a BoxedRoute, matchit::Params<'a, 'b>)> {
+		if method != Method::HEAD {
+			return None;
+		}
+
+		self.basic
+			.at(Some(&Method::GET), path)
+			.filter(|(route, _)| route.head_fallback())
+	}
+}
+
+impl Routes {
+	pub fn new() -> Self {
+		Self {
+			hosts: vec![HostRoutes::new(None)],
 			catcher: vec![],
+			middleware: vec![],
+			names: HashMap::new(),
+			info: vec![],
 		}
 	}
 
+	#[track_caller]
+	fn host_routes(&mut self, host: Option<&str>) -> &mut HostRoutes {
+		let pattern = host.map(HostPattern::parse);
+
+		let pos = match self.hosts.iter().position(|h| h.pattern == pattern) {
+			Some(pos) => pos,
+			None => {
+				let routes = HostRoutes::new(pattern);
+				// insert after all routes with the same priority
+				let pos = self
+					.hosts
+					.iter()
+					.position(|h| h.priority() > routes.priority())
+					.unwrap_or(self.hosts.len());
+				self.hosts.insert(pos, routes);
+				pos
+			}
+		};
+
+		&mut self.hosts[pos]
+	}
+
 	#[track_caller]
 	pub fn push_raw<R>(&mut self, path: RoutePath, route: R)
 	where
 		R: RawRoute + 'static,
 	{
-		self.raw
+		if let Some(name) = route.name() {
+			self.push_name(name, &path.path);
+		}
+
+		self.push_info(
+			&path,
+			route.host(),
+			route.name(),
+			route.kind(),
+			route.type_name(),
+		);
+
+		self.host_routes(route.host())
+			.raw
 			.insert(path.method.as_ref(), path.path, Box::new(route))
 			.unwrap();
 	}
@@ -51,11 +160,79 @@ impl Routes {
 	where
 		R: Route + 'static,
 	{
-		self.basic
+		if let Some(name) = route.name() {
+			self.push_name(name, &path.path);
+		}
+
+		self.push_info(
+			&path,
+			route.host(),
+			route.name(),
+			route.kind(),
+			route.type_name(),
+		);
+
+		self.host_routes(route.host())
+			.basic
 			.insert(path.method.as_ref(), path.path, Box::new(route))
 			.unwrap();
 	}
 
+	#[track_caller]
+	fn push_name(&mut self, name: &str, path: &str) {
+		let prev = self.names.insert(name.to_string(), path.to_string());
+		assert!(prev.is_none(), "route name {name:?} is already used");
+	}
+
+	fn push_info(
+		&mut self,
+		path: &RoutePath,
+		host: Option<&str>,
+		name: Option<&str>,
+		kind: RouteKind,
+		type_name: &'static str,
+	) {
+		let params = host
+			.into_iter()
+			.chain([&*path.path])
+			.flat_map(|template| {
+				ParamsNames::parse(template)
+					.iter()
+					.map(String::from)
+					.collect::<Vec<_>>()
+			})
+			.collect();
+
+		self.info.push(RouteInfo {
+			method: path.method.clone(),
+			path: path.path.to_string(),
+			host: host.map(Into::into),
+			params,
+			kind,
+			name: name.map(Into::into),
+			type_name,
+		});
+	}
+
+	/// Returns information about every route in the order they were added.
+	pub fn info(&self) -> &[RouteInfo] {
+		&self.info
+	}
+
+	/// Builds the path of the route with the given name.
+	pub fn url_for(
+		&self,
+		name: &str,
+		params: &[(&str, &str)],
+	) -> Result<String, UrlError> {
+		let template = self
+			.names
+			.get(name)
+			.ok_or_else(|| UrlError::UnknownRoute(name.to_string()))?;
+
+		fill_path(template, params)
+	}
+
 	pub fn push_catcher<C>(&mut self, catcher: C)
 	where
 		C: Catcher + 'static,
@@ -63,32 +240,81 @@ impl Routes {
 		self.catcher.push(Box::new(catcher))
 	}
 
+	pub fn push_middleware<M>(&mut self, middleware: M)
+	where
+		M: Middleware + 'static,
+	{
+		self.middleware.push(BoxedMiddleware::new(middleware))
+	}
+
+	/// Routes with a matching host pattern are tried first, the host should
+	/// not contain the port.
 	pub fn route_raw<'a>(
 		&'a self,
 		method: &Method,
+		host: Option<&str>,
 		path: &str,
 	) -> Option<(&'a BoxedRawRoute, PathParams)> {
-		let (route, params) = self
-			.raw
-			// first try with the correct method
-			.at(Some(method), path)
-			.or_else(|| self.raw.at(None, path))?;
+		self.hosts.iter().find_map(|routes| {
+			let host_params = routes.matches(host)?;
+			let (route, params) = routes
+				.raw
+				// first try with the correct method
+				.at(Some(method), path)
+				.or_else(|| routes.raw.at(None, path))?;
 
-		Some((route, PathParams::new(params)))
+			Some((route, PathParams::with_host(params, host_params)))
+		})
 	}
 
+	/// Routes with a matching host pattern are tried first, the host should
+	/// not contain the port.
 	pub fn route<'a>(
 		&'a self,
 		method: &Method,
+		host: Option<&str>,
 		path: &str,
 	) -> Option<(&'a BoxedRoute, PathParams)> {
-		let (route, params) = self
-			.basic
-			// first try with the correct method
-			.at(Some(method), path)
-			.or_else(|| self.basic.at(None, path))?;
+		self.hosts.iter().find_map(|routes| {
+			let host_params = routes.matches(host)?;
+			let (route, params) = routes
+				.basic
+				// first try with the correct method
+				.at(Some(method), path)
+				.or_else(|| routes.basic.at(None, path))
+				.or_else(|| routes.head_fallback(method, path))?;
+
+			Some((route, PathParams::with_host(params, host_params)))
+		})
+	}
+
+	/// Returns every method which has a route, raw or normal, matching the
+	/// host and path.
+	///
+	/// Routes without a method are not included.
+	pub fn allowed_methods(
+		&self,
+		host: Option<&str>,
+		path: &str,
+	) -> Vec<Method> {
+		router::METHODS
+			.iter()
+			.filter(|method| {
+				self.hosts
+					.iter()
+					.filter(|routes| routes.matches(host).is_some())
+					.any(|routes| {
+						routes.raw.at(Some(method), path).is_some()
+							|| routes.basic.at(Some(method), path).is_some()
+							|| routes.head_fallback(method, path).is_some()
+					})
+			})
+			.cloned()
+			.collect()
+	}
 
-		Some((route, PathParams::new(params)))
+	pub(crate) fn middlewares(&self) -> &[BoxedMiddleware] {
+		&self.middleware
 	}
 
 	pub fn catchers(&self) -> slice::Iter<'_, BoxedCatcher> {
@@ -1,7 +1,4 @@
-use std::{
-	collections::{HashMap, HashSet},
-	str::FromStr,
-};
+use std::{collections::HashMap, str::FromStr};
 
 use byte_parser::{ParseIterator, StrParser};
 use matchit::Params;
@@ -22,6 +19,20 @@ impl PathParams {
 		Self { inner }
 	}
 
+	/// The params of the path take precedence over the params of the host.
+	pub(crate) fn with_host(
+		params: Params,
+		host_params: Vec<(String, String)>,
+	) -> Self {
+		let mut me = Self::new(params);
+
+		for (key, value) in host_params {
+			me.inner.entry(key).or_insert(value);
+		}
+
+		me
+	}
+
 	pub fn exists(&self, key: impl AsRef<str>) -> bool {
 		self.inner.contains_key(key.as_ref())
 	}
@@ -57,14 +68,15 @@ impl PathParams {
 
 #[derive(Debug, Clone)]
 pub struct ParamsNames<'a> {
-	list: HashSet<&'a str>,
+	// in the order they appear, without duplicates
+	list: Vec<&'a str>,
 }
 
 impl<'a> ParamsNames<'a> {
 	pub fn parse(s: &'a str) -> Self {
 		let mut parser = StrParser::new(s);
 
-		let mut list = HashSet::new();
+		let mut list = Vec::new();
 
 		#[allow(clippy::never_loop)]
 		'template_loop: loop {
@@ -102,7 +114,9 @@ impl<'a> ParamsNames<'a> {
 							.trim_start_matches("*?")
 							// trim `{*name}`
 							.trim_start_matches('*');
-						list.insert(s);
+						if !list.contains(&s) {
+							list.push(s);
+						}
 
 						parser.next().unwrap();
 
@@ -118,10 +132,24 @@ impl<'a> ParamsNames<'a> {
 	}
 
 	pub fn exists(&self, key: impl AsRef<str>) -> bool {
-		self.list.contains(key.as_ref())
+		self.list.contains(&key.as_ref())
 	}
 
 	pub fn is_empty(&self) -> bool {
 		self.list.is_empty()
 	}
+
+	/// Adds the params of a host pattern, for example `{tenant}.example.com`.
+	pub fn extend_host(&mut self, host: &'a str) {
+		for name in ParamsNames::parse(host).list {
+			if !self.list.contains(&name) {
+				self.list.push(name);
+			}
+		}
+	}
+
+	/// Returns the names in the order they appear.
+	pub fn iter(&self) -> impl Iterator<Item = &'a str> + '_ {
+		self.list.iter().copied()
+	}
 }
@@ -5,7 +5,7 @@ use crate::{Resources, Response};
 
 pub use crate::server::{HyperBody, HyperRequest};
 
-use super::{ParamsNames, PathParams, RoutePath};
+use super::{ParamsNames, PathParams, RouteKind, RoutePath};
 
 /// A `RawRoute` is the more powerfull brother/sister to `Route`. It get's
 /// executed before `Route`.
@@ -18,6 +18,30 @@ pub trait RawRoute: Send + Sync {
 	// get's only called once
 	fn path(&self) -> RoutePath;
 
+	/// The name of the route, which can be used to build its url with
+	/// `Chuchi::url_for`.
+	fn name(&self) -> Option<&str> {
+		None
+	}
+
+	/// A host pattern the request needs to match, for example
+	/// `admin.example.com`, `*.example.com` or `{tenant}.example.com`.
+	///
+	/// Params of the host are available like params of the path.
+	fn host(&self) -> Option<&str> {
+		None
+	}
+
+	/// The kind of the route, used for `RouteInfo`.
+	fn kind(&self) -> RouteKind {
+		RouteKind::Raw
+	}
+
+	/// The rust type implementing the route, used for `RouteInfo`.
+	fn type_name(&self) -> &'static str {
+		std::any::type_name::<Self>()
+	}
+
 	fn call<'a>(
 		&'a self,
 		req: &'a mut HyperRequest,
@@ -1,9 +1,10 @@
-use super::{ParamsNames, PathParams};
+use super::{ParamsNames, PathParams, RouteKind};
 
 use crate::header::Method;
 use crate::util::PinnedFuture;
 use crate::{Request, Resources, Response};
 
+use std::any::TypeId;
 use std::borrow::Cow;
 
 #[derive(Debug, Clone)]
@@ -27,6 +28,42 @@ pub trait Route: Send + Sync {
 	// get's only called once
 	fn path(&self) -> RoutePath;
 
+	/// The name of the route, which can be used to build its url with
+	/// `Chuchi::url_for`.
+	fn name(&self) -> Option<&str> {
+		None
+	}
+
+	/// A host pattern the request needs to match, for example
+	/// `admin.example.com`, `*.example.com` or `{tenant}.example.com`.
+	///
+	/// Params of the host are available like params of the path.
+	fn host(&self) -> Option<&str> {
+		None
+	}
+
+	/// The kind of the route, used for `RouteInfo`.
+	fn kind(&self) -> RouteKind {
+		RouteKind::Basic
+	}
+
+	/// The rust type implementing the route, used for `RouteInfo`.
+	fn type_name(&self) -> &'static str {
+		std::any::type_name::<Self>()
+	}
+
+	/// Returns true if this `GET` route should also be called for `HEAD`
+	/// requests. The body of the response will not be sent.
+	fn head_fallback(&self) -> bool {
+		true
+	}
+
+	/// Returns true if the `Middleware` with the given `TypeId` should not
+	/// be called for this route.
+	fn skip_middleware(&self, _middleware: TypeId) -> bool {
+		false
+	}
+
 	fn call<'a>(
 		&'a self,
 		req: &'a mut Request,
@@ -17,6 +17,19 @@ fn method_to_num(method: Option<&Method>) -> usize {
 	}
 }
 
+/// All methods which have their own router, ordered by `method_to_num`.
+pub(super) static METHODS: [Method; 9] = [
+	Method::GET,
+	Method::POST,
+	Method::PUT,
+	Method::DELETE,
+	Method::HEAD,
+	Method::OPTIONS,
+	Method::CONNECT,
+	Method::PATCH,
+	Method::TRACE,
+];
+
 enum Route<T> {
 	Value(T),
 	// This needs to point to a route with a value
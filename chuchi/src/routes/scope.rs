@@ -0,0 +1,368 @@
+use super::host::HostPattern;
+use super::middleware::{BoxedMiddleware, Next};
+use super::{
+	Catcher, HyperRequest, Middleware, ParamsNames, PathParams, RawRoute,
+	Route, RouteKind, RoutePath,
+};
+
+use crate::header::{RequestHeader, ResponseHeader};
+use crate::into::IntoRoute;
+use crate::util::PinnedFuture;
+use crate::{Request, Resources, Response};
+
+use std::any::TypeId;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// A group of routes, raw routes, catchers and middlewares which get mounted
+/// under a common prefix.
+///
+/// The middlewares of a scope are only called for the routes of the scope,
+/// after the middlewares of the parent. Raw routes never call middlewares,
+/// not even the ones of the scope. Catchers are only called if the path
+/// of the request starts with the prefix.
+///
+/// With `Scope::host` every route of the scope only matches requests to the
+/// given host. A host set directly on a route takes precedence.
+///
+/// The requirements of every route and middleware get validated when the
+/// scope is mounted with `Chuchi::mount`.
+///
+/// ## Example
+/// ```
+/// use chuchi::routes::Scope;
+/// use chuchi::extractor::PathStr;
+/// use chuchi::get;
+///
+/// #[get("/users/{id}")]
+/// fn user(id: &PathStr) -> String {
+/// 	format!("user {id}")
+/// }
+///
+/// # async fn build() {
+/// let mut api = Scope::new();
+/// api.add_route(user);
+///
+/// let mut server = chuchi::build("127.0.0.1:0").await.unwrap();
+/// // the route can now be reached with /api/v2/users/{id}
+/// server.mount("/api/v2", api);
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Scope {
+	routes: Vec<Box<dyn Route>>,
+	raw_routes: Vec<Box<dyn RawRoute>>,
+	catchers: Vec<ScopedCatcher>,
+	middlewares: Vec<BoxedMiddleware>,
+	host: Option<String>,
+}
+
+impl Scope {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a `Route` to the scope.
+	pub fn add_route<R>(&mut self, route: R)
+	where
+		R: IntoRoute + 'static,
+	{
+		self.routes.push(Box::new(route.into_route()));
+	}
+
+	/// Adds a `RawRoute` to the scope.
+	///
+	/// ## Note
+	/// Raw routes get the prefix and the host of the scope but skip the
+	/// middlewares of the scope.
+	pub fn add_raw_route<R>(&mut self, route: R)
+	where
+		R: RawRoute + 'static,
+	{
+		self.raw_routes.push(Box::new(route));
+	}
+
+	/// Adds a `Catcher` to the scope.
+	pub fn add_catcher<C>(&mut self, catcher: C)
+	where
+		C: Catcher + 'static,
+	{
+		self.catchers.push(ScopedCatcher {
+			prefix: String::new(),
+			host: None,
+			catcher: Box::new(catcher),
+		});
+	}
+
+	/// Adds a `Middleware` to the scope.
+	///
+	/// Middlewares are called in the order they were added.
+	pub fn add_middleware<M>(&mut self, middleware: M)
+	where
+		M: Middleware + 'static,
+	{
+		self.middlewares.push(BoxedMiddleware::new(middleware));
+	}
+
+	/// Only matches requests to the given host pattern, for example
+	/// `admin.example.com`, `*.example.com` or `{tenant}.example.com`.
+	///
+	/// ## Panics
+	/// If the pattern is invalid.
+	#[track_caller]
+	pub fn host(&mut self, pattern: impl Into<String>) {
+		let pattern = pattern.into();
+		// validate the pattern early
+		HostPattern::parse(&pattern);
+		self.host = Some(pattern);
+	}
+
+	/// Mounts another scope under the given prefix.
+	pub fn mount(&mut self, prefix: &str, scope: Scope) {
+		let (routes, raw_routes, catchers) = scope.into_scoped(prefix);
+
+		self.routes
+			.extend(routes.map(|r| Box::new(r) as Box<dyn Route>));
+		self.raw_routes
+			.extend(raw_routes.map(|r| Box::new(r) as Box<dyn RawRoute>));
+		self.catchers.extend(catchers);
+	}
+
+	/// Returns every route, raw route and catcher with the prefix applied.
+	pub(crate) fn into_scoped(
+		self,
+		prefix: &str,
+	) -> (
+		impl Iterator<Item = ScopedRoute>,
+		impl Iterator<Item = ScopedRawRoute>,
+		impl Iterator<Item = ScopedCatcher>,
+	) {
+		let prefix = prefix.trim_end_matches('/').to_string();
+		let middlewares: Arc<[BoxedMiddleware]> = self.middlewares.into();
+		let host: Option<Arc<str>> = self.host.map(Into::into);
+
+		let routes = {
+			let prefix = prefix.clone();
+			let host = host.clone();
+			self.routes.into_iter().map(move |route| {
+				let mut path = route.path();
+				path.path = join_path(&prefix, &path.path).into();
+
+				ScopedRoute {
+					path,
+					host: host.clone(),
+					route,
+					middlewares: middlewares.clone(),
+				}
+			})
+		};
+
+		let raw_routes = {
+			let prefix = prefix.clone();
+			let host = host.clone();
+			self.raw_routes.into_iter().map(move |route| {
+				let mut path = route.path();
+				path.path = join_path(&prefix, &path.path).into();
+
+				ScopedRawRoute {
+					path,
+					host: host.clone(),
+					route,
+				}
+			})
+		};
+
+		let host = host.map(|h| HostPattern::parse(&h));
+		let catchers = self.catchers.into_iter().map(move |mut catcher| {
+			catcher.prefix = join_path(&prefix, &catcher.prefix);
+			// the host of a nested scope takes precedence
+			if catcher.host.is_none() {
+				catcher.host.clone_from(&host);
+			}
+			catcher
+		});
+
+		(routes, raw_routes, catchers)
+	}
+}
+
+fn join_path(prefix: &str, path: &str) -> String {
+	assert!(
+		prefix.is_empty() || prefix.starts_with('/'),
+		"prefix {prefix:?} needs to start with a slash"
+	);
+
+	// the root of a scope is the prefix itself, /api instead of /api/
+	if path == "/" && !prefix.is_empty() {
+		return prefix.to_string();
+	}
+
+	format!("{prefix}{path}")
+}
+
+pub(crate) struct ScopedRoute {
+	// the path including the prefix
+	path: RoutePath,
+	host: Option<Arc<str>>,
+	route: Box<dyn Route>,
+	middlewares: Arc<[BoxedMiddleware]>,
+}
+
+impl Route for ScopedRoute {
+	fn validate_requirements(
+		&self,
+		params: &ParamsNames,
+		resources: &Resources,
+	) {
+		for middleware in self.middlewares.iter() {
+			middleware.validate_requirements(resources);
+		}
+
+		self.route.validate_requirements(params, resources);
+	}
+
+	fn path(&self) -> RoutePath {
+		self.path.clone()
+	}
+
+	fn name(&self) -> Option<&str> {
+		self.route.name()
+	}
+
+	fn host(&self) -> Option<&str> {
+		self.route.host().or(self.host.as_deref())
+	}
+
+	fn kind(&self) -> RouteKind {
+		self.route.kind()
+	}
+
+	fn type_name(&self) -> &'static str {
+		self.route.type_name()
+	}
+
+	fn head_fallback(&self) -> bool {
+		self.route.head_fallback()
+	}
+
+	fn skip_middleware(&self, middleware: TypeId) -> bool {
+		self.route.skip_middleware(middleware)
+	}
+
+	fn call<'a>(
+		&'a self,
+		req: &'a mut Request,
+		params: &'a PathParams,
+		resources: &'a Resources,
+	) -> PinnedFuture<'a, crate::Result<Response>> {
+		if self.middlewares.is_empty() {
+			return self.route.call(req, params, resources);
+		}
+
+		Next::new_route(&self.middlewares, &*self.route, params, resources)
+			.run(req)
+	}
+}
+
+pub(crate) struct ScopedRawRoute {
+	// the path including the prefix
+	path: RoutePath,
+	host: Option<Arc<str>>,
+	route: Box<dyn RawRoute>,
+}
+
+impl RawRoute for ScopedRawRoute {
+	fn validate_requirements(&self, params: &ParamsNames, data: &Resources) {
+		self.route.validate_requirements(params, data);
+	}
+
+	fn path(&self) -> RoutePath {
+		self.path.clone()
+	}
+
+	fn name(&self) -> Option<&str> {
+		self.route.name()
+	}
+
+	fn host(&self) -> Option<&str> {
+		self.route.host().or(self.host.as_deref())
+	}
+
+	fn kind(&self) -> RouteKind {
+		self.route.kind()
+	}
+
+	fn type_name(&self) -> &'static str {
+		self.route.type_name()
+	}
+
+	fn call<'a>(
+		&'a self,
+		req: &'a mut HyperRequest,
+		address: SocketAddr,
+		params: &'a PathParams,
+		resources: &'a Resources,
+	) -> PinnedFuture<'a, Option<crate::Result<Response>>> {
+		self.route.call(req, address, params, resources)
+	}
+}
+
+pub(crate) struct ScopedCatcher {
+	// an empty prefix matches every request
+	prefix: String,
+	// None matches every host
+	host: Option<HostPattern>,
+	catcher: Box<dyn Catcher>,
+}
+
+impl ScopedCatcher {
+	/// Returns true if the path is inside the prefix, params in the prefix
+	/// match any segment.
+	fn matches(&self, path: &str) -> bool {
+		let mut path = path.split('/');
+
+		for prefix in self.prefix.split('/') {
+			let Some(segment) = path.next() else {
+				return false;
+			};
+
+			let is_param = prefix.starts_with('{') && prefix.ends_with('}');
+			if is_param && prefix.starts_with("{*") {
+				return true;
+			}
+
+			if !is_param && prefix != segment {
+				return false;
+			}
+		}
+
+		true
+	}
+}
+
+impl Catcher for ScopedCatcher {
+	fn check(&self, req: &RequestHeader, res: &ResponseHeader) -> bool {
+		let host_matches = match (&self.host, req.uri().host()) {
+			(None, _) => true,
+			(Some(pattern), Some(host)) => pattern.matches(host).is_some(),
+			(Some(_), None) => false,
+		};
+
+		host_matches
+			&& self.matches(req.uri().path())
+			&& self.catcher.check(req, res)
+	}
+
+	fn validate_data(&self, data: &Resources) {
+		self.catcher.validate_data(data);
+	}
+
+	fn call<'a>(
+		&'a self,
+		req: &'a mut Request,
+		resp: &'a mut Response,
+		data: &'a Resources,
+	) -> PinnedFuture<'a, crate::Result<()>> {
+		self.catcher.call(req, resp, data)
+	}
+}
@@ -0,0 +1,114 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+/// Characters which need to be encoded in a path segment.
+const SEGMENT: &AsciiSet = &CONTROLS
+	.add(b' ')
+	.add(b'"')
+	.add(b'#')
+	.add(b'%')
+	.add(b'/')
+	.add(b'<')
+	.add(b'>')
+	.add(b'?')
+	.add(b'`')
+	.add(b'{')
+	.add(b'}');
+
+/// A catch all param may contain multiple segments.
+const REST: &AsciiSet = &SEGMENT.remove(b'/');
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum UrlError {
+	#[error("no route with the name {0:?} exists")]
+	UnknownRoute(String),
+	#[error("the param {0:?} is missing")]
+	MissingParam(String),
+	#[error("the param in the template {0:?} is not closed")]
+	UnclosedParam(String),
+}
+
+/// Builds a path from a route template, for example `/users/{id}`.
+///
+/// Every value is percent-encoded, the value of a catch all param (`{*rest}`)
+/// may contain slashes. An optional catch all param (`{*?rest}`) can be
+/// omitted. The segments `.` and `..` are encoded as `%2E` and `%2E%2E` so
+/// they don't get resolved by clients.
+///
+/// ## Example
+/// ```
+/// use chuchi::routes::fill_path;
+///
+/// let path = fill_path("/users/{id}/{*file}", &[
+/// 	("id", "a b"),
+/// 	("file", "dir/file.txt"),
+/// ]);
+/// assert_eq!(path.unwrap(), "/users/a%20b/dir/file.txt");
+/// ```
+pub fn fill_path(
+	template: &str,
+	params: &[(&str, &str)],
+) -> Result<String, UrlError> {
+	let mut path = String::with_capacity(template.len());
+	let mut chars = template.chars();
+
+	while let Some(c) = chars.next() {
+		match c {
+			// escaped braces
+			'{' | '}' if chars.as_str().starts_with(c) => {
+				chars.next();
+				path.push(c);
+			}
+			'{' => {
+				let rest = chars.as_str();
+				let end = rest.find('}').ok_or_else(|| {
+					UrlError::UnclosedParam(template.to_string())
+				})?;
+				let param = &rest[..end];
+				chars = rest[end + 1..].chars();
+
+				let value = |name: &str| {
+					params.iter().find(|(k, _)| *k == name).map(|(_, v)| *v)
+				};
+
+				if let Some(name) = param.strip_prefix("*?") {
+					push_rest(&mut path, value(name).unwrap_or(""));
+					continue;
+				}
+
+				let (name, rest) = match param.strip_prefix('*') {
+					Some(name) => (name, true),
+					None => (param, false),
+				};
+
+				let value = value(name)
+					.ok_or_else(|| UrlError::MissingParam(name.to_string()))?;
+				if rest {
+					push_rest(&mut path, value);
+				} else {
+					push_segment(&mut path, value, SEGMENT);
+				}
+			}
+			c => path.push(c),
+		}
+	}
+
+	Ok(path)
+}
+
+fn push_rest(path: &mut String, value: &str) {
+	for (i, segment) in value.split('/').enumerate() {
+		if i > 0 {
+			path.push('/');
+		}
+		push_segment(path, segment, REST);
+	}
+}
+
+fn push_segment(path: &mut String, segment: &str, set: &'static AsciiSet) {
+	match segment {
+		"." => path.push_str("%2E"),
+		".." => path.push_str("%2E%2E"),
+		s => path.extend(utf8_percent_encode(s, set)),
+	}
+}
@@ -1,19 +1,22 @@
-use crate::routes::Routes;
-use crate::server::HyperBody;
+use crate::routes::{request_host, Next, Routes};
+use crate::server::HyperRequest;
+#[cfg(debug_assertions)]
+use crate::util::validate_content_length;
 use crate::util::{
 	convert_chuchi_resp_to_hyper_resp, convert_hyper_req_to_chuchi_req,
 };
-use crate::{Error, Request, Resources};
+use crate::{Body, Error, Request, Resources};
 
 use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::time::Duration;
 
-use hyper::body::Incoming;
 use tracing::{error, info, info_span, warn, Instrument};
 
 use crate::body::BodyHttp;
-use crate::header::StatusCode;
+use crate::header::{
+	Method, RequestHeader, ResponseHeader, StatusCode, ALLOW, CONTENT_LENGTH,
+};
 use crate::Response;
 
 const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
@@ -25,6 +28,8 @@ pub(crate) struct RequestConfigs {
 	pub timeout: Duration,
 	// in bytes
 	pub size_limit: usize,
+	#[cfg(feature = "decompression")]
+	pub decompress: bool,
 }
 
 impl RequestConfigs {
@@ -32,6 +37,8 @@ impl RequestConfigs {
 		Self {
 			timeout: DEFAULT_REQUEST_TIMEOUT,
 			size_limit: DEFAULT_REQUEST_SIZE_LIMIT,
+			#[cfg(feature = "decompression")]
+			decompress: false,
 		}
 	}
 
@@ -45,6 +52,11 @@ impl RequestConfigs {
 		assert!(size_limit > 0, "size limit needs to be bigger than zero");
 		self.size_limit = size_limit;
 	}
+
+	#[cfg(feature = "decompression")]
+	pub fn decompress(&mut self, decompress: bool) {
+		self.decompress = decompress;
+	}
 }
 
 // IncredientsForAChuchi
@@ -83,7 +95,7 @@ impl ServerShared {
 #[cfg(feature = "sentry")]
 pub(crate) async fn route_hyper(
 	wood: &ServerShared,
-	hyper_req: hyper::Request<Incoming>,
+	hyper_req: HyperRequest,
 	address: SocketAddr,
 ) -> Result<hyper::Response<BodyHttp>, Infallible> {
 	use std::sync::Arc;
@@ -100,7 +112,7 @@ pub(crate) async fn route_hyper(
 #[cfg(not(feature = "sentry"))]
 pub(crate) async fn route_hyper(
 	wood: &ServerShared,
-	hyper_req: hyper::Request<Incoming>,
+	hyper_req: HyperRequest,
 	address: SocketAddr,
 ) -> Result<hyper::Response<BodyHttp>, Infallible> {
 	route_hyper_with_span(wood, hyper_req, address).await
@@ -108,7 +120,7 @@ pub(crate) async fn route_hyper(
 
 async fn route_hyper_with_span(
 	wood: &ServerShared,
-	hyper_req: hyper::Request<Incoming>,
+	hyper_req: HyperRequest,
 	address: SocketAddr,
 ) -> Result<hyper::Response<BodyHttp>, Infallible> {
 	let span = info_span!(
@@ -124,13 +136,13 @@ async fn route_hyper_with_span(
 
 async fn route_hyper_inner(
 	wood: &ServerShared,
-	hyper_req: hyper::Request<Incoming>,
+	hyper_req: HyperRequest,
 	address: SocketAddr,
 ) -> Result<hyper::Response<BodyHttp>, Infallible> {
 	let method = hyper_req.method().clone();
 	let uri = hyper_req.uri().clone();
 	info!(?method, ?uri, "req");
-	let resp = route_hyper_req(wood, hyper_req, address).await;
+	let mut resp = route_hyper_req(wood, hyper_req, address).await;
 	let status_code = resp.header().status_code;
 
 	if status_code.is_server_error() {
@@ -141,6 +153,14 @@ async fn route_hyper_inner(
 		info!(?status_code, "{method} {uri} | {status_code}");
 	}
 
+	// debug_checks
+	#[cfg(debug_assertions)]
+	let _ = validate_content_length(&resp);
+
+	if method == Method::HEAD {
+		strip_head_body(&mut resp);
+	}
+
 	let hyper_resp = convert_chuchi_resp_to_hyper_resp(resp);
 
 	Ok(hyper_resp)
@@ -148,17 +168,16 @@ async fn route_hyper_inner(
 
 async fn route_hyper_req(
 	wood: &ServerShared,
-	hyper_req: hyper::Request<Incoming>,
+	mut hyper_req: HyperRequest,
 	address: SocketAddr,
 ) -> Response {
-	let mut hyper_req = hyper_req.map(HyperBody::from);
-
 	// route raw_routes
 	// response is Option<Response>
-	let resp = if let Some((route, params)) = wood
-		.routes()
-		.route_raw(hyper_req.method(), hyper_req.uri().path())
-	{
+	let resp = if let Some((route, params)) = wood.routes().route_raw(
+		hyper_req.method(),
+		request_host(&hyper_req),
+		hyper_req.uri().path(),
+	) {
 		let res = route
 			.call(&mut hyper_req, address, &params, wood.data())
 			.await;
@@ -187,46 +206,123 @@ async fn route_hyper_req(
 		}
 	};
 
-	// normal route
+	// middlewares and normal route
 	let mut resp = if let Some(r) = resp {
 		r
 	} else {
-		match route(wood, &mut req).await {
-			Some(Ok(resp)) => resp,
-			Some(Err(error)) => {
+		let next = Next::new(wood.routes(), wood.data());
+		match next.run(&mut req).await {
+			Ok(resp) => resp,
+			Err(error) => {
 				error!(?error, "route error");
 				error.status_code().into()
 			}
-			None => StatusCode::NOT_FOUND.into(),
 		}
 	};
 
-	// APPLY OVERRIDES
+	apply_catchers(wood, &mut req, &mut resp).await;
+
+	resp
+}
+
+/// Routes the request like the server does, but without raw routes.
+///
+/// Returns `None` if neither a route nor another method of a route matches
+/// the request.
+pub(crate) async fn route(
+	wood: &ServerShared,
+	req: &mut Request,
+) -> Option<Result<Response, Error>> {
+	let header = req.header();
+	let uri = header.uri();
+	let routes = wood.routes();
+
+	let matches = routes
+		.route(&header.method, uri.host(), uri.path())
+		.is_some();
+	if !matches && routes.allowed_methods(uri.host(), uri.path()).is_empty() {
+		return None;
+	}
+
+	let is_head = header.method == Method::HEAD;
+
+	let mut resp = match Next::new(routes, wood.data()).run(req).await {
+		Ok(resp) => resp,
+		Err(e) => return Some(Err(e)),
+	};
+
+	apply_catchers(wood, req, &mut resp).await;
+
+	if is_head {
+		strip_head_body(&mut resp);
+	}
+
+	Some(Ok(resp))
+}
 
-	// check with catcher
+async fn apply_catchers(
+	wood: &ServerShared,
+	req: &mut Request,
+	resp: &mut Response,
+) {
 	for catcher in wood.routes().catchers() {
 		if !catcher.check(req.header(), resp.header()) {
 			continue;
 		}
 
-		if let Err(e) = catcher.call(&mut req, &mut resp, wood.data()).await {
-			resp = e.status_code().into();
+		if let Err(e) = catcher.call(req, resp, wood.data()).await {
+			*resp = e.status_code().into();
 		}
 	}
+}
 
-	resp
+/// A HEAD response contains the same headers as a GET response but without
+/// the body.
+fn strip_head_body(resp: &mut Response) {
+	let body = resp.take_body();
+	if let Some(len) = body.len() {
+		if resp.header().value(CONTENT_LENGTH).is_none() {
+			resp.header.values.insert(CONTENT_LENGTH, len);
+		}
+	}
 }
 
-pub(crate) async fn route(
-	wood: &ServerShared,
-	req: &mut Request,
-) -> Option<Result<Response, Error>> {
-	// first response
-	let (route, params) = wood
-		.routes()
-		.route(&req.header().method, req.header().uri().path())?;
+/// Returns the response if no route matched the request.
+///
+/// If other methods match the path a `405 Method Not Allowed` is returned
+/// with an `Allow` header, `OPTIONS` requests get a `204 No Content`.
+pub(crate) fn no_route_response(
+	routes: &Routes,
+	header: &RequestHeader,
+) -> Response {
+	let uri = header.uri();
+	let mut methods = routes.allowed_methods(uri.host(), uri.path());
+	if methods.is_empty() {
+		return StatusCode::NOT_FOUND.into();
+	}
+
+	if !methods.contains(&Method::OPTIONS) {
+		methods.push(Method::OPTIONS);
+	}
 
-	let r = route.call(req, &params, wood.data()).await;
+	let allow = methods
+		.iter()
+		.map(Method::as_str)
+		.collect::<Vec<_>>()
+		.join(", ");
+
+	let mut resp = if header.method == Method::OPTIONS {
+		// a 204 should not contain a content-length header
+		let header = ResponseHeader {
+			status_code: StatusCode::NO_CONTENT,
+			..Default::default()
+		};
+		Response::new(header, Body::new())
+	} else {
+		StatusCode::METHOD_NOT_ALLOWED.into()
+	};
 
-	Some(r)
+	resp.header.values.insert(ALLOW, allow);
+
+	resp
 }
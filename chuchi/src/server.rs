@@ -3,11 +3,14 @@ use crate::util::PinnedFuture;
 use crate::{ChuchiShared, Error, Result};
 
 use std::convert::Infallible;
+use std::fmt;
+use std::future::Future;
 use std::net::SocketAddr;
 use std::pin::Pin;
 use std::result::Result as StdResult;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use crate::body::BodyHttp;
 
@@ -17,13 +20,27 @@ use hyper::{Request, Response};
 
 pub type HyperRequest = hyper::Request<HyperBody>;
 
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type BoxedHttpBody =
+	Pin<Box<dyn Body<Data = hyper::body::Bytes, Error = BoxError> + Send>>;
+
 use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+#[cfg(any(feature = "http1", feature = "http2"))]
+use tokio::sync::watch;
+use tokio::task::{JoinError, JoinHandle};
+
+#[cfg(feature = "tls")]
+use tokio_rustls::{rustls::ServerConfig, TlsAcceptor};
 
 // todo replace this function once hyper-util is ready
 pub(crate) struct Server {
 	listener: TcpListener,
 	#[allow(dead_code)]
 	shared: Arc<ServerShared>,
+	shutdown_timeout: Duration,
+	#[cfg(feature = "tls")]
+	tls: Option<TlsAcceptor>,
 }
 
 impl Server {
@@ -35,41 +52,211 @@ impl Server {
 			.await
 			.map_err(Error::from_server_error)?;
 
-		Ok(Self { listener, shared })
+		Ok(Self {
+			listener,
+			shared,
+			shutdown_timeout: Duration::from_secs(30),
+			#[cfg(feature = "tls")]
+			tls: None,
+		})
+	}
+
+	/// How long open connections get to finish after a shutdown was
+	/// requested.
+	pub fn set_shutdown_timeout(&mut self, timeout: Duration) {
+		self.shutdown_timeout = timeout;
+	}
+
+	/// Every accepted connection will first need to complete a tls handshake.
+	#[cfg(feature = "tls")]
+	pub fn set_tls(&mut self, config: Arc<ServerConfig>) {
+		self.tls = Some(TlsAcceptor::from(config));
 	}
 
 	pub fn local_addr(&self) -> Result<SocketAddr> {
 		self.listener.local_addr().map_err(Error::from_server_error)
 	}
 
+	/// Accepts connections until the `signal` resolves, then waits for the
+	/// open connections to finish.
 	#[cfg(any(feature = "http1", feature = "http2"))]
-	pub async fn serve(self) -> Result<()> {
-		use hyper_util::rt::{TokioExecutor, TokioIo};
-		use hyper_util::server::conn::auto::Builder;
+	pub async fn serve_with_shutdown<F>(self, signal: F) -> Result<Shutdown>
+	where
+		F: Future<Output = ()>,
+	{
+		use tokio::task::JoinSet;
 
-		loop {
-			let (stream, address) = self
-				.listener
-				.accept()
-				.await
-				.map_err(Error::from_server_error)?;
+		let (shutdown_tx, shutdown_rx) = watch::channel(false);
+		let mut connections = JoinSet::new();
+
+		tokio::pin!(signal);
 
-			let io = TokioIo::new(stream);
+		loop {
+			let (stream, address) = tokio::select! {
+				res = self.listener.accept() => match res {
+					Ok(conn) => conn,
+					Err(e) => {
+						// keep the behaviour of not touching connections
+						// which are already being served
+						connections.detach_all();
+						return Err(Error::from_server_error(e));
+					}
+				},
+				_ = &mut signal => break,
+				// remove finished connections so the set does not grow
+				Some(_) = connections.join_next() => continue
+			};
 
 			let service = ChuchiService {
 				shared: self.shared.clone(),
 				address,
 			};
+			let shutdown = shutdown_rx.clone();
+
+			#[cfg(feature = "tls")]
+			if let Some(tls) = &self.tls {
+				let tls = tls.clone();
+
+				// the handshake is done in the task to not block accepting
+				// new connections
+				connections.spawn(async move {
+					let mut shutdown = shutdown;
+
+					let handshake = tokio::select! {
+						res = tls.accept(stream) => res,
+						// a connection which is still in the handshake has
+						// no requests which need to finish
+						Ok(_) = shutdown.wait_for(|shutdown| *shutdown) => {
+							return
+						}
+					};
+
+					match handshake {
+						Ok(stream) => {
+							serve_connection(stream, service, shutdown).await
+						}
+						Err(e) => {
+							tracing::warn!(error = ?e, "tls handshake failed")
+						}
+					}
+				});
+
+				continue;
+			}
+
+			connections.spawn(serve_connection(stream, service, shutdown));
+		}
 
-			tokio::task::spawn(async move {
-				if let Err(err) = Builder::new(TokioExecutor::new())
-					.serve_connection_with_upgrades(io, service)
-					.await
-				{
-					tracing::error!(error = ?err, "Error serving connection: {err}");
-				}
-			});
+		// stop accepting new connections
+		drop(self.listener);
+
+		let _ = shutdown_tx.send(true);
+		let open = connections.len();
+
+		let _ = tokio::time::timeout(self.shutdown_timeout, async {
+			while connections.join_next().await.is_some() {}
+		})
+		.await;
+
+		let force_closed = connections.len();
+		connections.abort_all();
+
+		if force_closed > 0 {
+			tracing::warn!("force closed {force_closed} connections");
+		}
+
+		Ok(Shutdown {
+			graceful: open - force_closed,
+			force_closed,
+		})
+	}
+}
+
+#[cfg(any(feature = "http1", feature = "http2"))]
+async fn serve_connection<I>(
+	io: I,
+	service: ChuchiService,
+	mut shutdown: watch::Receiver<bool>,
+) where
+	I: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin + 'static,
+{
+	use hyper_util::rt::{TokioExecutor, TokioIo};
+	use hyper_util::server::conn::auto::Builder;
+
+	let builder = Builder::new(TokioExecutor::new());
+	let conn =
+		builder.serve_connection_with_upgrades(TokioIo::new(io), service);
+	tokio::pin!(conn);
+
+	let res = tokio::select! {
+		res = conn.as_mut() => res,
+		Ok(()) = async {
+			shutdown.wait_for(|shutdown| *shutdown).await.map(drop)
+		} => {
+			// lets the current requests finish and then closes the
+			// connection
+			conn.as_mut().graceful_shutdown();
+			conn.await
 		}
+	};
+
+	if let Err(err) = res {
+		tracing::error!(error = ?err, "Error serving connection: {err}");
+	}
+}
+
+/// A summary of what happened to the open connections during a shutdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Shutdown {
+	/// Connections which finished before the drain timeout.
+	pub graceful: usize,
+	/// Connections which were still open after the drain timeout and
+	/// got closed.
+	pub force_closed: usize,
+}
+
+/// A handle to a server running on a tokio task.
+///
+/// Awaiting the handle waits until the server stops, dropping it does not
+/// stop the server.
+pub struct ChuchiTask {
+	handle: JoinHandle<Shutdown>,
+	shutdown: oneshot::Sender<()>,
+}
+
+impl ChuchiTask {
+	pub(crate) fn new(
+		handle: JoinHandle<Shutdown>,
+		shutdown: oneshot::Sender<()>,
+	) -> Self {
+		Self { handle, shutdown }
+	}
+
+	/// Stops accepting new connections and waits until the open connections
+	/// are closed or the drain timeout is reached.
+	pub async fn shutdown(self) -> StdResult<Shutdown, JoinError> {
+		let _ = self.shutdown.send(());
+		self.handle.await
+	}
+
+	/// Aborts the task without waiting for open connections.
+	pub fn abort(&self) {
+		self.handle.abort()
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.handle.is_finished()
+	}
+}
+
+impl Future for ChuchiTask {
+	type Output = StdResult<(), JoinError>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+		Pin::new(&mut self.get_mut().handle)
+			.poll(cx)
+			.map(|r| r.map(|_| ()))
 	}
 }
 
@@ -97,11 +284,47 @@ impl Service<Request<Incoming>> for ChuchiService {
 		let wood = self.shared.clone();
 		let address = self.address;
 		PinnedFuture::new(async move {
+			routing::route_hyper(&wood, req.map(HyperBody::from), address).await
+		})
+	}
+}
+
+/// Allows to use tower layers or to mount chuchi inside another tower based
+/// framework.
+#[cfg(feature = "tower")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+impl<B> tower_service::Service<Request<B>> for ChuchiService
+where
+	B: Body<Data = hyper::body::Bytes> + Send + 'static,
+	B::Error: Into<BoxError>,
+{
+	type Response = Response<BodyHttp>;
+	type Error = Infallible;
+	type Future = PinnedFuture<'static, StdResult<Self::Response, Self::Error>>;
+
+	fn poll_ready(
+		&mut self,
+		_cx: &mut Context<'_>,
+	) -> Poll<StdResult<(), Self::Error>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, req: Request<B>) -> Self::Future {
+		let wood = self.shared.clone();
+		let address = self.address;
+		PinnedFuture::new(async move {
+			let req = req.map(HyperBody::from_http_body);
 			routing::route_hyper(&wood, req, address).await
 		})
 	}
 }
 
+/// The body of a `HyperRequest`.
+///
+/// ## Note
+/// The `Error` of the `http_body::Body` implementation is a boxed error and
+/// no longer `hyper::Error`, since the body of a request passed to the tower
+/// `Service` can have any error type.
 #[derive(Debug)]
 pub struct HyperBody {
 	inner: InnerBody,
@@ -112,20 +335,49 @@ impl HyperBody {
 		Self::default()
 	}
 
+	/// Creates a `HyperBody` from any type that implements
+	/// `http_body::Body`.
+	#[cfg(feature = "tower")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+	pub fn from_http_body<B>(body: B) -> Self
+	where
+		B: Body<Data = hyper::body::Bytes> + Send + 'static,
+		B::Error: Into<BoxError>,
+	{
+		use http_body_util::BodyExt;
+
+		Self {
+			inner: InnerBody::Boxed(Box::pin(body.map_err(Into::into))),
+		}
+	}
+
 	pub fn take(&mut self) -> Self {
 		std::mem::take(self)
 	}
 }
 
-#[derive(Debug)]
 enum InnerBody {
 	Empty,
 	Incoming(Incoming),
+	#[cfg_attr(not(feature = "tower"), allow(dead_code))]
+	Boxed(BoxedHttpBody),
+}
+
+impl fmt::Debug for InnerBody {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Empty => f.write_str("Empty"),
+			Self::Incoming(inc) => {
+				f.debug_tuple("Incoming").field(inc).finish()
+			}
+			Self::Boxed(_) => f.write_str("Boxed"),
+		}
+	}
 }
 
 impl Body for HyperBody {
 	type Data = hyper::body::Bytes;
-	type Error = hyper::Error;
+	type Error = BoxError;
 
 	fn poll_frame(
 		self: Pin<&mut Self>,
@@ -133,7 +385,10 @@ impl Body for HyperBody {
 	) -> Poll<Option<StdResult<Frame<Self::Data>, Self::Error>>> {
 		match &mut self.get_mut().inner {
 			InnerBody::Empty => Poll::Ready(None),
-			InnerBody::Incoming(inc) => Pin::new(inc).poll_frame(cx),
+			InnerBody::Incoming(inc) => {
+				Pin::new(inc).poll_frame(cx).map_err(Into::into)
+			}
+			InnerBody::Boxed(body) => body.as_mut().poll_frame(cx),
 		}
 	}
 
@@ -141,6 +396,7 @@ impl Body for HyperBody {
 		match &self.inner {
 			InnerBody::Empty => true,
 			InnerBody::Incoming(inc) => inc.is_end_stream(),
+			InnerBody::Boxed(body) => body.is_end_stream(),
 		}
 	}
 
@@ -148,6 +404,7 @@ impl Body for HyperBody {
 		match &self.inner {
 			InnerBody::Empty => SizeHint::default(),
 			InnerBody::Incoming(inc) => inc.size_hint(),
+			InnerBody::Boxed(body) => body.size_hint(),
 		}
 	}
 }
@@ -173,6 +430,7 @@ impl From<HyperBody> for crate::Body {
 		match hyper_body.inner {
 			InnerBody::Empty => Self::new(),
 			InnerBody::Incoming(inc) => Self::from(inc),
+			InnerBody::Boxed(body) => Self::from_http_body(body),
 		}
 	}
 }
@@ -0,0 +1,418 @@
+//! Server side sessions.
+//!
+//! The [`Sessions`] middleware loads the session of every request from a
+//! [`SessionStore`] and saves it after the route finished, setting the
+//! session cookie if necessary. Routes access the session with the
+//! [`Session`] extractor.
+//!
+//! ## Example
+//! ```
+//! use chuchi::session::{MemoryStore, Session, Sessions};
+//! use chuchi::get;
+//!
+//! use std::time::Duration;
+//!
+//! #[get("/login")]
+//! fn login(session: Session) -> &'static str {
+//! 	// prevents session fixation
+//! 	session.regenerate();
+//! 	session.insert("user", &"alice").unwrap();
+//! 	"logged in"
+//! }
+//!
+//! #[get("/user")]
+//! fn user(session: Session) -> String {
+//! 	session
+//! 		.get::<String>("user")
+//! 		.unwrap_or_else(|| "not logged in".into())
+//! }
+//!
+//! # async fn build() {
+//! let mut server = chuchi::build("127.0.0.1:0").await.unwrap();
+//! server.add_middleware(
+//! 	Sessions::new(MemoryStore::new())
+//! 		.idle_timeout(Duration::from_secs(30 * 60)),
+//! );
+//! server.add_route(login);
+//! server.add_route(user);
+//! # }
+//! ```
+
+mod store;
+pub use store::{FileStore, MemoryStore, SessionRecord, SessionStore};
+
+use crate::error::{ErrorKind, ServerErrorKind};
+use crate::extractor::{Extractor, ExtractorError};
+use crate::header::{Cookie, SameSite, SET_COOKIE};
+use crate::routes::{Middleware, Next};
+use crate::util::PinnedFuture;
+use crate::{Request, Resources, Response};
+
+use std::error::Error as StdError;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+tokio::task_local! {
+	static CURRENT: Session;
+}
+
+/// A `Middleware` which loads and stores the `Session` of every request.
+///
+/// The session cookie is `HttpOnly`, uses `SameSite=Lax` and the path `/`
+/// by default. It does not set a `Max-Age`, so the browser forgets it when
+/// it is closed, the expiry of the session is checked on the server.
+pub struct Sessions {
+	store: Arc<dyn SessionStore>,
+	cookie_name: String,
+	cookie_path: String,
+	secure: bool,
+	same_site: SameSite,
+	idle_timeout: Option<Duration>,
+	absolute_timeout: Option<Duration>,
+}
+
+impl Sessions {
+	/// Creates a new `Sessions` middleware using the cookie
+	/// `chuchi_session`.
+	pub fn new<S>(store: S) -> Self
+	where
+		S: SessionStore + 'static,
+	{
+		Self {
+			store: Arc::new(store),
+			cookie_name: "chuchi_session".into(),
+			cookie_path: "/".into(),
+			secure: false,
+			same_site: SameSite::Lax,
+			idle_timeout: None,
+			absolute_timeout: None,
+		}
+	}
+
+	/// Sets the name of the session cookie.
+	///
+	/// ## Panics
+	/// If the name is not a valid cookie name.
+	#[track_caller]
+	pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+		let name = name.into();
+		// validate the name early
+		Cookie::new(name.as_str(), "");
+		self.cookie_name = name;
+		self
+	}
+
+	/// Sets the path of the session cookie.
+	///
+	/// ## Panics
+	/// If the path is not a valid cookie path.
+	#[track_caller]
+	pub fn cookie_path(mut self, path: impl Into<String>) -> Self {
+		let path = path.into();
+		// validate the path early
+		Cookie::builder("a", "").path(path.as_str());
+		self.cookie_path = path;
+		self
+	}
+
+	/// Only sends the session cookie over https.
+	pub fn secure(mut self) -> Self {
+		self.secure = true;
+		self
+	}
+
+	/// Sets the `SameSite` attribute of the session cookie.
+	pub fn same_site(mut self, same_site: SameSite) -> Self {
+		self.same_site = same_site;
+		self
+	}
+
+	/// Expires a session if it was not used for the given duration.
+	pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+		self.idle_timeout = Some(timeout);
+		self
+	}
+
+	/// Expires a session after the given duration, regardless of its use.
+	pub fn absolute_timeout(mut self, timeout: Duration) -> Self {
+		self.absolute_timeout = Some(timeout);
+		self
+	}
+
+	fn is_expired(&self, record: &SessionRecord) -> bool {
+		let elapsed = |time: SystemTime| {
+			SystemTime::now().duration_since(time).unwrap_or_default()
+		};
+
+		self.idle_timeout
+			.is_some_and(|t| elapsed(record.last_access()) > t)
+			|| self
+				.absolute_timeout
+				.is_some_and(|t| elapsed(record.created()) > t)
+	}
+
+	async fn load(&self, id: Option<String>) -> crate::Result<SessionState> {
+		let Some(id) = id else {
+			return Ok(SessionState::new(None, SessionRecord::new()));
+		};
+
+		let record = self
+			.store
+			.load(&id)
+			.await
+			.map_err(crate::Error::from_server_error)?;
+
+		match record {
+			Some(record) if !self.is_expired(&record) => {
+				Ok(SessionState::new(Some(id), record))
+			}
+			Some(_) => {
+				self.store
+					.remove(&id)
+					.await
+					.map_err(crate::Error::from_server_error)?;
+
+				Ok(SessionState::new(None, SessionRecord::new()))
+			}
+			None => Ok(SessionState::new(None, SessionRecord::new())),
+		}
+	}
+
+	async fn save(
+		&self,
+		state: SessionState,
+		resp: &mut Response,
+	) -> crate::Result<()> {
+		let SessionState {
+			loaded_id,
+			mut record,
+			changed,
+			regenerate,
+			destroyed,
+		} = state;
+
+		let remove_loaded = destroyed || regenerate;
+		if let Some(id) = loaded_id.as_ref().filter(|_| remove_loaded) {
+			self.store
+				.remove(id)
+				.await
+				.map_err(crate::Error::from_server_error)?;
+		}
+
+		if destroyed {
+			if loaded_id.is_some() {
+				let cookie = Cookie::removal(self.cookie_name.as_str())
+					.path(self.cookie_path.as_str())
+					.build();
+				resp.header
+					.values
+					.append(SET_COOKIE, cookie.to_header_value());
+			}
+
+			return Ok(());
+		}
+
+		let id = match loaded_id {
+			Some(id) if !regenerate => id,
+			// a new session without data does not need to be stored
+			_ if record.data().is_empty() => return Ok(()),
+			_ => {
+				let id = generate_id();
+				resp.header
+					.values
+					.append(SET_COOKIE, self.cookie(&id).to_header_value());
+				id
+			}
+		};
+
+		// the last access only needs to be updated if it is used
+		if changed || regenerate || self.idle_timeout.is_some() {
+			record.touch();
+			self.store
+				.store(&id, &record)
+				.await
+				.map_err(crate::Error::from_server_error)?;
+		}
+
+		Ok(())
+	}
+
+	fn cookie(&self, id: &str) -> Cookie {
+		let builder = Cookie::builder(self.cookie_name.as_str(), id)
+			.path(self.cookie_path.as_str())
+			.http_only()
+			.same_site(self.same_site);
+
+		if self.secure {
+			builder.secure().build()
+		} else {
+			builder.build()
+		}
+	}
+}
+
+impl Middleware for Sessions {
+	fn call<'a>(
+		&'a self,
+		req: &'a mut Request,
+		_resources: &'a Resources,
+		next: Next<'a>,
+	) -> PinnedFuture<'a, crate::Result<Response>> {
+		PinnedFuture::new(async move {
+			let id = req
+				.header()
+				.cookies()
+				.get(&self.cookie_name)
+				.map(ToString::to_string);
+
+			let session = Session {
+				inner: Arc::new(Mutex::new(self.load(id).await?)),
+			};
+
+			let mut resp =
+				CURRENT.scope(session.clone(), next.run(req)).await?;
+
+			let state = session.lock().clone();
+			self.save(state, &mut resp).await?;
+
+			Ok(resp)
+		})
+	}
+}
+
+fn generate_id() -> String {
+	let bytes: [u8; 32] = rand::random();
+	URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[derive(Debug, Clone)]
+struct SessionState {
+	// the id the session was loaded with
+	loaded_id: Option<String>,
+	record: SessionRecord,
+	changed: bool,
+	regenerate: bool,
+	destroyed: bool,
+}
+
+impl SessionState {
+	fn new(loaded_id: Option<String>, record: SessionRecord) -> Self {
+		Self {
+			loaded_id,
+			record,
+			changed: false,
+			regenerate: false,
+			destroyed: false,
+		}
+	}
+}
+
+/// The session of the current request.
+///
+/// Values are stored as json. Changes are saved after the route finished,
+/// if the route returns an error nothing gets saved.
+///
+/// Needs the `Sessions` middleware.
+#[derive(Debug, Clone)]
+pub struct Session {
+	inner: Arc<Mutex<SessionState>>,
+}
+
+impl Session {
+	fn lock(&self) -> MutexGuard<'_, SessionState> {
+		self.inner.lock().unwrap()
+	}
+
+	/// Returns the id of the session, `None` if the session is new.
+	pub fn id(&self) -> Option<String> {
+		self.lock().loaded_id.clone()
+	}
+
+	/// Returns the value if it exists and can be deserialized.
+	pub fn get<T>(&self, key: &str) -> Option<T>
+	where
+		T: DeserializeOwned,
+	{
+		let value = self.lock().record.data().get(key)?.clone();
+		serde_json::from_value(value).ok()
+	}
+
+	/// Inserts a value, replacing the previous one.
+	pub fn insert<T>(&self, key: &str, value: &T) -> serde_json::Result<()>
+	where
+		T: Serialize + ?Sized,
+	{
+		let value = serde_json::to_value(value)?;
+
+		let mut state = self.lock();
+		state.record.data_mut().insert(key.to_string(), value);
+		state.changed = true;
+
+		Ok(())
+	}
+
+	/// Removes a value, returns `true` if it existed.
+	pub fn remove(&self, key: &str) -> bool {
+		let mut state = self.lock();
+		let existed = state.record.data_mut().remove(key).is_some();
+		state.changed |= existed;
+
+		existed
+	}
+
+	/// Removes every value.
+	pub fn clear(&self) {
+		let mut state = self.lock();
+		state.record.data_mut().clear();
+		state.changed = true;
+	}
+
+	/// Moves the data to a new session id.
+	///
+	/// Should be called when the privileges change, for example after a
+	/// login.
+	pub fn regenerate(&self) {
+		self.lock().regenerate = true;
+	}
+
+	/// Removes the session from the store and tells the client to remove
+	/// the session cookie.
+	pub fn destroy(&self) {
+		let mut state = self.lock();
+		state.record.data_mut().clear();
+		state.destroyed = true;
+	}
+}
+
+impl<'a, R> Extractor<'a, R> for Session {
+	type Error = SessionsMissing;
+	type Prepared = Session;
+
+	extractor_validate!();
+
+	extractor_prepare!(|_prepare| {
+		CURRENT.try_with(Clone::clone).map_err(|_| SessionsMissing)
+	});
+
+	extractor_extract!(|extract| { Ok(extract.prepared) });
+}
+
+/// The `Session` extractor was used without the `Sessions` middleware.
+#[derive(Debug, thiserror::Error)]
+#[error("the Sessions middleware is missing")]
+pub struct SessionsMissing;
+
+impl ExtractorError for SessionsMissing {
+	fn error_kind(&self) -> ErrorKind {
+		ErrorKind::Server(ServerErrorKind::InternalServerError)
+	}
+
+	fn into_std(self) -> Box<dyn StdError + Send + Sync> {
+		Box::new(self)
+	}
+}
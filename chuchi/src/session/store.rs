@@ -0,0 +1,229 @@
+use crate::util::PinnedFuture;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Stores the data of every session.
+///
+/// The store does not know about expiry, expired sessions are removed by
+/// `Sessions` once they get loaded.
+pub trait SessionStore: Send + Sync {
+	/// Loads the session with the given id.
+	///
+	/// Returns `None` if the session does not exist.
+	fn load<'a>(
+		&'a self,
+		id: &'a str,
+	) -> PinnedFuture<'a, io::Result<Option<SessionRecord>>>;
+
+	/// Stores the session, replacing the previous one with the same id.
+	fn store<'a>(
+		&'a self,
+		id: &'a str,
+		record: &'a SessionRecord,
+	) -> PinnedFuture<'a, io::Result<()>>;
+
+	/// Removes the session, a missing session is not an error.
+	fn remove<'a>(&'a self, id: &'a str) -> PinnedFuture<'a, io::Result<()>>;
+}
+
+/// The data of a session together with its timestamps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+	// unix timestamps in milliseconds
+	created: u64,
+	last_access: u64,
+	data: HashMap<String, Value>,
+}
+
+impl SessionRecord {
+	pub(crate) fn new() -> Self {
+		let now = unix_millis(SystemTime::now());
+
+		Self {
+			created: now,
+			last_access: now,
+			data: HashMap::new(),
+		}
+	}
+
+	/// Returns when the session was created.
+	pub fn created(&self) -> SystemTime {
+		UNIX_EPOCH + Duration::from_millis(self.created)
+	}
+
+	/// Returns when the session was last used.
+	pub fn last_access(&self) -> SystemTime {
+		UNIX_EPOCH + Duration::from_millis(self.last_access)
+	}
+
+	pub(crate) fn touch(&mut self) {
+		self.last_access = unix_millis(SystemTime::now());
+	}
+
+	pub(crate) fn data(&self) -> &HashMap<String, Value> {
+		&self.data
+	}
+
+	pub(crate) fn data_mut(&mut self) -> &mut HashMap<String, Value> {
+		&mut self.data
+	}
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis() as u64)
+		.unwrap_or(0)
+}
+
+/// A `SessionStore` which keeps every session in memory.
+///
+/// Every session is lost when the server restarts.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+	sessions: Mutex<HashMap<String, SessionRecord>>,
+}
+
+impl MemoryStore {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+impl SessionStore for MemoryStore {
+	fn load<'a>(
+		&'a self,
+		id: &'a str,
+	) -> PinnedFuture<'a, io::Result<Option<SessionRecord>>> {
+		let record = self.sessions.lock().unwrap().get(id).cloned();
+		PinnedFuture::new(async move { Ok(record) })
+	}
+
+	fn store<'a>(
+		&'a self,
+		id: &'a str,
+		record: &'a SessionRecord,
+	) -> PinnedFuture<'a, io::Result<()>> {
+		self.sessions
+			.lock()
+			.unwrap()
+			.insert(id.to_string(), record.clone());
+		PinnedFuture::new(async move { Ok(()) })
+	}
+
+	fn remove<'a>(&'a self, id: &'a str) -> PinnedFuture<'a, io::Result<()>> {
+		self.sessions.lock().unwrap().remove(id);
+		PinnedFuture::new(async move { Ok(()) })
+	}
+}
+
+/// A `SessionStore` which saves every session as a json file in a
+/// directory.
+///
+/// The directory gets created when the first session is stored. A session is
+/// first written to a temporary file which then replaces the old one, so a
+/// crash never leaves a partially written session behind. A session which
+/// cannot be parsed is treated as missing and removed.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+	dir: PathBuf,
+}
+
+impl FileStore {
+	pub fn new(dir: impl Into<PathBuf>) -> Self {
+		Self { dir: dir.into() }
+	}
+
+	/// Returns the path of the session file, `None` if the id could be used
+	/// to escape the directory.
+	fn path(&self, id: &str) -> Option<PathBuf> {
+		let valid = !id.is_empty()
+			&& id
+				.bytes()
+				.all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+
+		valid.then(|| self.dir.join(format!("{id}.json")))
+	}
+}
+
+impl SessionStore for FileStore {
+	fn load<'a>(
+		&'a self,
+		id: &'a str,
+	) -> PinnedFuture<'a, io::Result<Option<SessionRecord>>> {
+		PinnedFuture::new(async move {
+			let Some(path) = self.path(id) else {
+				return Ok(None);
+			};
+
+			let bytes = match tokio::fs::read(&path).await {
+				Ok(b) => b,
+				Err(e) if e.kind() == io::ErrorKind::NotFound => {
+					return Ok(None)
+				}
+				Err(e) => return Err(e),
+			};
+
+			match serde_json::from_slice(&bytes) {
+				Ok(record) => Ok(Some(record)),
+				Err(e) => {
+					tracing::warn!("removing corrupt session {path:?}: {e}");
+					let _ = tokio::fs::remove_file(path).await;
+					Ok(None)
+				}
+			}
+		})
+	}
+
+	fn store<'a>(
+		&'a self,
+		id: &'a str,
+		record: &'a SessionRecord,
+	) -> PinnedFuture<'a, io::Result<()>> {
+		PinnedFuture::new(async move {
+			let path = self.path(id).ok_or_else(|| {
+				io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"invalid session id",
+				)
+			})?;
+			let bytes = serde_json::to_vec(record)?;
+
+			tokio::fs::create_dir_all(&self.dir).await?;
+
+			// the temporary file is in the same directory so the rename
+			// cannot fail because of different filesystems
+			let suffix: u64 = rand::random();
+			let tmp = self.dir.join(format!(".{id}.{suffix:016x}.tmp"));
+			let res = match tokio::fs::write(&tmp, bytes).await {
+				Ok(_) => tokio::fs::rename(&tmp, path).await,
+				Err(e) => Err(e),
+			};
+
+			if res.is_err() {
+				let _ = tokio::fs::remove_file(&tmp).await;
+			}
+
+			res
+		})
+	}
+
+	fn remove<'a>(&'a self, id: &'a str) -> PinnedFuture<'a, io::Result<()>> {
+		PinnedFuture::new(async move {
+			let Some(path) = self.path(id) else {
+				return Ok(());
+			};
+
+			match tokio::fs::remove_file(path).await {
+				Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+				_ => Ok(()),
+			}
+		})
+	}
+}
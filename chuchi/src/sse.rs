@@ -0,0 +1,348 @@
+//! Server-Sent Events.
+//!
+//! An [`EventStream`] keeps the response open and sends every [`Event`] to
+//! the client as soon as it is available. Clients which reconnect send the
+//! id of the last event they received, which can be read with the
+//! [`LastEventId`] extractor.
+//!
+//! ## Example
+//! ```
+//! use chuchi::sse::{Event, EventStream, LastEventId};
+//! use chuchi::get;
+//!
+//! use std::time::Duration;
+//!
+//! #[get("/ticks")]
+//! fn ticks(last_id: LastEventId) -> EventStream {
+//! 	// resume after the last event the client received
+//! 	let start: u64 = last_id
+//! 		.as_str()
+//! 		.and_then(|id| id.parse().ok())
+//! 		.map_or(0, |id: u64| id + 1);
+//!
+//! 	let (sender, stream) = EventStream::channel(16);
+//!
+//! 	tokio::spawn(async move {
+//! 		for i in start.. {
+//! 			let event = Event::new()
+//! 				.event("tick")
+//! 				.id(i.to_string())
+//! 				.data(format!("tick number {i}"));
+//!
+//! 			// the client disconnected
+//! 			if sender.send(event).await.is_err() {
+//! 				break;
+//! 			}
+//!
+//! 			tokio::time::sleep(Duration::from_secs(1)).await;
+//! 		}
+//! 	});
+//!
+//! 	stream
+//! }
+//!
+//! # async fn build() {
+//! let mut server = chuchi::build("127.0.0.1:0").await.unwrap();
+//! server.add_route(ticks);
+//! # }
+//! ```
+
+use crate::extractor::Extractor;
+use crate::header::CACHE_CONTROL;
+use crate::into::IntoResponse;
+use crate::{Body, Response};
+
+use std::convert::Infallible;
+use std::fmt::{self, Write};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_core::Stream;
+use tokio::sync::mpsc;
+use tokio::time::{Instant, Sleep};
+
+const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// A single event which can be sent to the client.
+///
+/// Every field is optional, an event without data only updates the state of
+/// the client (for example the `retry` interval) and is not dispatched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+	event: Option<String>,
+	id: Option<String>,
+	retry: Option<Duration>,
+	data: Option<String>,
+}
+
+impl Event {
+	/// Creates an empty event.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the name of the event, without a name the client dispatches a
+	/// `message` event.
+	///
+	/// ## Panics
+	/// If the name contains a line break.
+	#[track_caller]
+	pub fn event(mut self, event: impl Into<String>) -> Self {
+		let event = event.into();
+		assert!(
+			!event.contains(['\r', '\n']),
+			"an event name cannot contain line breaks"
+		);
+		self.event = Some(event);
+		self
+	}
+
+	/// Sets the id of the event, which the client sends back in the
+	/// `Last-Event-ID` header when it reconnects.
+	///
+	/// ## Panics
+	/// If the id contains a line break or a null character.
+	#[track_caller]
+	pub fn id(mut self, id: impl Into<String>) -> Self {
+		let id = id.into();
+		assert!(
+			!id.contains(['\r', '\n', '\0']),
+			"an event id cannot contain line breaks or null characters"
+		);
+		self.id = Some(id);
+		self
+	}
+
+	/// Sets how long the client should wait before reconnecting.
+	pub fn retry(mut self, retry: Duration) -> Self {
+		self.retry = Some(retry);
+		self
+	}
+
+	/// Sets the data of the event, which can span multiple lines.
+	pub fn data(mut self, data: impl Into<String>) -> Self {
+		self.data = Some(data.into());
+		self
+	}
+
+	/// Sets the data of the event to the value serialized as json.
+	#[cfg(feature = "json")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+	pub fn json<T>(self, value: &T) -> serde_json::Result<Self>
+	where
+		T: serde::Serialize + ?Sized,
+	{
+		Ok(self.data(serde_json::to_string(value)?))
+	}
+}
+
+/// Formats the event as it is sent to the client, including the blank line
+/// at the end.
+impl fmt::Display for Event {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Some(event) = &self.event {
+			writeln!(f, "event: {event}")?;
+		}
+
+		if let Some(id) = &self.id {
+			writeln!(f, "id: {id}")?;
+		}
+
+		if let Some(retry) = &self.retry {
+			writeln!(f, "retry: {}", retry.as_millis())?;
+		}
+
+		if let Some(data) = &self.data {
+			// every line break type needs to start a new data field
+			let data = data.replace("\r\n", "\n");
+			for line in data.split(['\r', '\n']) {
+				writeln!(f, "data: {line}")?;
+			}
+		}
+
+		f.write_char('\n')
+	}
+}
+
+/// A response which sends events to the client until the stream of events
+/// ends or the client disconnects.
+///
+/// A keep-alive comment is sent every 15 seconds if there was no other
+/// event, which prevents proxies from closing the connection.
+pub struct EventStream {
+	events: Pin<Box<dyn Stream<Item = Event> + Send + Sync>>,
+	keep_alive: Option<Duration>,
+}
+
+impl EventStream {
+	/// Creates an `EventStream` from a stream of events.
+	pub fn new<S>(events: S) -> Self
+	where
+		S: Stream<Item = Event> + Send + Sync + 'static,
+	{
+		Self {
+			events: Box::pin(events),
+			keep_alive: Some(DEFAULT_KEEP_ALIVE),
+		}
+	}
+
+	/// Creates an `EventStream` together with an `EventSender` which can
+	/// send events to it.
+	///
+	/// `buffer` is the amount of events which can be queued before `send`
+	/// waits.
+	///
+	/// ## Panics
+	/// If the buffer is zero.
+	#[track_caller]
+	pub fn channel(buffer: usize) -> (EventSender, Self) {
+		let (tx, rx) = mpsc::channel(buffer);
+
+		(EventSender { inner: tx }, Self::new(ReceiverStream(rx)))
+	}
+
+	/// Sets the interval of the keep-alive comments, `None` disables them.
+	pub fn keep_alive(mut self, interval: Option<Duration>) -> Self {
+		self.keep_alive = interval;
+		self
+	}
+}
+
+impl fmt::Debug for EventStream {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("EventStream")
+			.field("keep_alive", &self.keep_alive)
+			.finish_non_exhaustive()
+	}
+}
+
+impl IntoResponse for EventStream {
+	fn into_response(self) -> Response {
+		let keep_alive = self
+			.keep_alive
+			.map(|interval| (interval, Box::pin(tokio::time::sleep(interval))));
+
+		let body = EncodedEvents {
+			events: self.events,
+			keep_alive,
+		};
+
+		Response::builder()
+			.content_type("text/event-stream")
+			.header(CACHE_CONTROL, "no-cache")
+			.body(Body::from_async_bytes_streamer(body))
+			.build()
+	}
+}
+
+/// Sends events to an `EventStream`.
+#[derive(Debug, Clone)]
+pub struct EventSender {
+	inner: mpsc::Sender<Event>,
+}
+
+impl EventSender {
+	/// Sends an event, waits if the buffer is full.
+	///
+	/// Returns an error if the client disconnected.
+	pub async fn send(&self, event: Event) -> Result<(), EventStreamClosed> {
+		self.inner.send(event).await.map_err(|_| EventStreamClosed)
+	}
+
+	/// Returns `true` if the client disconnected.
+	pub fn is_closed(&self) -> bool {
+		self.inner.is_closed()
+	}
+}
+
+/// The `EventStream` was dropped, most likely because the client
+/// disconnected.
+#[derive(Debug, thiserror::Error)]
+#[error("the event stream was closed")]
+pub struct EventStreamClosed;
+
+/// The value of the `Last-Event-ID` header.
+///
+/// Is sent by the client when it reconnects, so the stream can be resumed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastEventId(Option<String>);
+
+impl LastEventId {
+	pub fn as_str(&self) -> Option<&str> {
+		self.0.as_deref()
+	}
+
+	pub fn into_inner(self) -> Option<String> {
+		self.0
+	}
+}
+
+impl<'a, R> Extractor<'a, R> for LastEventId {
+	type Error = Infallible;
+	type Prepared = LastEventId;
+
+	extractor_validate!();
+
+	extractor_prepare!(|prepare| {
+		let id = prepare.header.value("last-event-id");
+		Ok(LastEventId(id.map(ToString::to_string)))
+	});
+
+	extractor_extract!(|extract| { Ok(extract.prepared) });
+}
+
+struct ReceiverStream(mpsc::Receiver<Event>);
+
+impl Stream for ReceiverStream {
+	type Item = Event;
+
+	fn poll_next(
+		self: Pin<&mut Self>,
+		cx: &mut Context,
+	) -> Poll<Option<Event>> {
+		self.get_mut().0.poll_recv(cx)
+	}
+}
+
+struct EncodedEvents {
+	events: Pin<Box<dyn Stream<Item = Event> + Send + Sync>>,
+	keep_alive: Option<(Duration, Pin<Box<Sleep>>)>,
+}
+
+impl Stream for EncodedEvents {
+	type Item = io::Result<Bytes>;
+
+	fn poll_next(
+		self: Pin<&mut Self>,
+		cx: &mut Context,
+	) -> Poll<Option<Self::Item>> {
+		let me = self.get_mut();
+
+		match me.events.as_mut().poll_next(cx) {
+			Poll::Ready(Some(event)) => {
+				if let Some((interval, sleep)) = &mut me.keep_alive {
+					sleep.as_mut().reset(Instant::now() + *interval);
+				}
+
+				return Poll::Ready(Some(Ok(event.to_string().into())));
+			}
+			Poll::Ready(None) => return Poll::Ready(None),
+			Poll::Pending => {}
+		}
+
+		if let Some((interval, sleep)) = &mut me.keep_alive {
+			if sleep.as_mut().poll(cx).is_ready() {
+				sleep.as_mut().reset(Instant::now() + *interval);
+				return Poll::Ready(Some(Ok(Bytes::from_static(
+					b": keep-alive\n\n",
+				))));
+			}
+		}
+
+		Poll::Pending
+	}
+}
@@ -0,0 +1,43 @@
+//! Types needed to configure tls.
+//!
+//! Tls is terminated with rustls, see `Chuchi::tls`.
+
+use crate::{Error, Result};
+
+use std::sync::Arc;
+
+pub use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+pub use tokio_rustls::rustls::ServerConfig;
+
+use tokio_rustls::rustls::crypto::ring;
+
+/// Creates a `ServerConfig` which negotiates every http version that is
+/// enabled via features.
+pub(crate) fn server_config(
+	cert_chain: Vec<CertificateDer<'static>>,
+	private_key: PrivateKeyDer<'static>,
+) -> Result<ServerConfig> {
+	let mut config =
+		ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+			.with_safe_default_protocol_versions()
+			.map_err(Error::from_server_error)?
+			.with_no_client_auth()
+			.with_single_cert(cert_chain, private_key)
+			.map_err(Error::from_server_error)?;
+
+	config.alpn_protocols = alpn_protocols();
+
+	Ok(config)
+}
+
+fn alpn_protocols() -> Vec<Vec<u8>> {
+	// the order defines the preference
+	let protocols: &[&[u8]] = &[
+		#[cfg(feature = "http2")]
+		b"h2",
+		#[cfg(feature = "http1")]
+		b"http/1.1",
+	];
+
+	protocols.iter().map(|p| p.to_vec()).collect()
+}
@@ -0,0 +1,150 @@
+//! Interoperability with tower.
+//!
+//! [`ChuchiService`](crate::service::ChuchiService) implements
+//! `tower::Service`, which allows to wrap it in tower layers or to mount
+//! chuchi inside another tower based framework.
+//!
+//! With [`TowerRoute`] any tower service can be mounted at a path prefix.
+
+use crate::header::{ContentType, HeaderValues, ResponseHeader};
+use crate::routes::{
+	HyperBody, HyperRequest, ParamsNames, PathParams, RawRoute, RoutePath,
+};
+use crate::util::PinnedFuture;
+use crate::{Body, Error, Resources, Response};
+
+use std::borrow::Cow;
+use std::error::Error as StdError;
+use std::future::poll_fn;
+use std::mem;
+use std::net::SocketAddr;
+
+use hyper::body::{Body as HttpBody, Bytes};
+use hyper::Uri;
+
+use tower_service::Service;
+
+/// A `RawRoute` which forwards every request below a prefix to a tower
+/// service.
+///
+/// The prefix is removed from the path before the request is passed to the
+/// service, so a request to `/prefix/a?b=c` is seen as `/a?b=c`.
+///
+/// ## Example
+/// ```
+/// use chuchi::tower::TowerRoute;
+///
+/// #[tokio::main]
+/// async fn main() {
+/// 	let mut server = chuchi::build("127.0.0.1:0").await.unwrap();
+///
+/// 	let service = tower::service_fn(|_req| async {
+/// 		Ok::<_, std::convert::Infallible>(hyper::Response::new(
+/// 			"Hello from tower".to_string(),
+/// 		))
+/// 	});
+///
+/// 	server.add_raw_route(TowerRoute::new("/tower", service));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TowerRoute<S> {
+	prefix: Cow<'static, str>,
+	service: S,
+}
+
+impl<S> TowerRoute<S> {
+	/// Creates a new `TowerRoute` which handles every request matching the
+	/// prefix.
+	pub fn new(prefix: impl Into<Cow<'static, str>>, service: S) -> Self {
+		Self {
+			prefix: prefix.into(),
+			service,
+		}
+	}
+}
+
+impl<S, B> RawRoute for TowerRoute<S>
+where
+	S: Service<HyperRequest, Response = hyper::Response<B>>
+		+ Clone
+		+ Send
+		+ Sync
+		+ 'static,
+	S::Future: Send,
+	S::Error: Into<Box<dyn StdError + Send + Sync>>,
+	B: HttpBody<Data = Bytes> + Send + 'static,
+	B::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+	fn validate_requirements(&self, _params: &ParamsNames, _data: &Resources) {}
+
+	fn path(&self) -> RoutePath {
+		RoutePath {
+			method: None,
+			path: format!("{}/{{*?rem}}", self.prefix.trim_end_matches('/'))
+				.into(),
+		}
+	}
+
+	fn call<'a>(
+		&'a self,
+		req: &'a mut HyperRequest,
+		_address: SocketAddr,
+		params: &'a PathParams,
+		_data: &'a Resources,
+	) -> PinnedFuture<'a, Option<crate::Result<Response>>> {
+		let mut service = self.service.clone();
+
+		PinnedFuture::new(async move {
+			let uri = match strip_prefix(req.uri(), params.get("rem")) {
+				Ok(uri) => uri,
+				Err(e) => return Some(Err(Error::from_server_error(e))),
+			};
+
+			let mut req =
+				mem::replace(req, hyper::Request::new(HyperBody::new()));
+			*req.uri_mut() = uri;
+
+			let res = async {
+				poll_fn(|cx| service.poll_ready(cx)).await?;
+				service.call(req).await
+			}
+			.await;
+
+			Some(res.map(convert_response).map_err(Error::from_server_error))
+		})
+	}
+}
+
+fn strip_prefix(
+	uri: &Uri,
+	rem: Option<&str>,
+) -> Result<Uri, hyper::http::Error> {
+	let path = format!("/{}", rem.unwrap_or(""));
+	let path_and_query = match uri.query() {
+		Some(query) => format!("{path}?{query}"),
+		None => path,
+	};
+
+	let mut parts = uri.clone().into_parts();
+	parts.path_and_query = Some(path_and_query.parse()?);
+
+	Ok(Uri::from_parts(parts)?)
+}
+
+fn convert_response<B>(resp: hyper::Response<B>) -> Response
+where
+	B: HttpBody<Data = Bytes> + Send + 'static,
+	B::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+	let (parts, body) = resp.into_parts();
+
+	let header = ResponseHeader {
+		status_code: parts.status,
+		// the content-type stays in the values
+		content_type: ContentType::None,
+		values: HeaderValues::from_inner(parts.headers),
+	};
+
+	Response::new(header, Body::from_http_body(body))
+}
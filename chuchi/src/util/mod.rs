@@ -1,4 +1,4 @@
-use crate::header::{ContentType, CONTENT_TYPE};
+use crate::header::{ContentType, HeaderValues, CONTENT_TYPE, VARY};
 use crate::routing::RequestConfigs;
 use crate::server::HyperRequest;
 use crate::{Body, Request, Response};
@@ -64,19 +64,81 @@ pub(crate) fn convert_hyper_req_to_chuchi_req(
 	body.set_size_limit(Some(configs.size_limit));
 	body.set_timeout(Some(configs.timeout));
 
-	let header = convert_hyper_parts_to_chuchi_header(parts, address)?;
+	#[allow(unused_mut)]
+	let mut header = convert_hyper_parts_to_chuchi_header(parts, address)?;
+
+	#[cfg(feature = "decompression")]
+	if configs.decompress {
+		decompress_body(&mut header, &mut body);
+	}
 
 	Ok(Request::new(header, body))
 }
 
+#[cfg(feature = "decompression")]
+fn decompress_body(header: &mut RequestHeader, body: &mut Body) {
+	use crate::body::ContentEncoding;
+	use crate::header::{CONTENT_ENCODING, CONTENT_LENGTH};
+
+	// multiple encodings are not supported
+	if header.values.get_all(CONTENT_ENCODING).count() != 1 {
+		return;
+	}
+
+	let encoding = header
+		.values
+		.get_str(CONTENT_ENCODING)
+		.and_then(ContentEncoding::from_header);
+
+	if let Some(encoding) = encoding {
+		body.decompress(encoding);
+		// the body is no longer encoded and its length is unknown
+		header.values.remove(CONTENT_ENCODING);
+		header.values.remove(CONTENT_LENGTH);
+	}
+}
+
+/// Adds the header name to the `Vary` header if it is not already present.
+pub(crate) fn add_vary(values: &mut HeaderValues, name: &str) {
+	let already_set = values.get_all(VARY).any(|v| {
+		v.to_str().is_ok_and(|v| {
+			v.split(',').any(|v| {
+				let v = v.trim();
+				v == "*" || v.eq_ignore_ascii_case(name)
+			})
+		})
+	});
+
+	if !already_set {
+		values.append(VARY, name.to_string());
+	}
+}
+
+/// Parses an item of a list with q-values, for example `br;q=0.8` of the
+/// `Accept-Encoding` header.
+#[cfg(any(feature = "compression", feature = "fs"))]
+pub(crate) fn parse_quality(item: &str) -> Option<(&str, f32)> {
+	let mut parts = item.split(';');
+	let token = parts.next()?.trim();
+	if token.is_empty() {
+		return None;
+	}
+
+	let q = parts
+		.filter_map(|p| {
+			let (k, v) = p.split_once('=')?;
+			k.trim().eq_ignore_ascii_case("q").then(|| v.trim())
+		})
+		.next()
+		.map_or(Some(1.0), |q| q.parse().ok())?;
+
+	Some((token, q))
+}
+
 // // Response
 pub(crate) fn convert_chuchi_resp_to_hyper_resp(
 	response: Response,
 ) -> hyper::Response<BodyHttp> {
-	// debug_checks
-	#[cfg(debug_assertions)]
-	let _ = validate_content_length(&response);
-
 	let mut header = response.header;
 
 	if !matches!(header.content_type, ContentType::None) {
@@ -96,7 +158,7 @@ pub(crate) fn convert_chuchi_resp_to_hyper_resp(
 }
 
 #[cfg(debug_assertions)]
-fn validate_content_length(response: &Response) -> Option<()> {
+pub(crate) fn validate_content_length(response: &Response) -> Option<()> {
 	let len = response.header().value(crate::header::CONTENT_LENGTH)?;
 
 	let len: usize = len.parse().expect("content-length not a number");
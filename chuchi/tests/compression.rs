@@ -0,0 +1,165 @@
+use chuchi::compression::{Compression, Encoding};
+use chuchi::header::{Mime, ETAG};
+use chuchi::{get, Body, Response};
+
+use std::pin::Pin;
+
+use async_compression::tokio::bufread::{
+	BrotliDecoder, GzipDecoder, ZstdDecoder,
+};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+#[macro_use]
+mod util;
+
+fn text() -> String {
+	"Hello, compression! ".repeat(200)
+}
+
+#[get("/text")]
+fn large_text() -> String {
+	text()
+}
+
+#[get("/small")]
+fn small() -> &'static str {
+	"too small"
+}
+
+#[get("/image")]
+fn image() -> Response {
+	Response::builder()
+		.content_type(Mime::PNG)
+		.body(vec![0u8; 4096])
+		.build()
+}
+
+#[get("/etag")]
+fn etag() -> Response {
+	Response::builder()
+		.header(ETAG, "\"abc\"")
+		.body(text())
+		.build()
+}
+
+#[get("/stream")]
+fn stream() -> Response {
+	let reader = std::io::Cursor::new(text().into_bytes());
+	Response::builder()
+		.content_type(Mime::TEXT)
+		.body(Body::from_async_reader(reader))
+		.build()
+}
+
+async fn decode(encoding: &str, data: Vec<u8>) -> String {
+	let data = &data[..];
+	let mut reader: Pin<Box<dyn AsyncRead>> = match encoding {
+		"gzip" => Box::pin(GzipDecoder::new(data)),
+		"br" => Box::pin(BrotliDecoder::new(data)),
+		"zstd" => Box::pin(ZstdDecoder::new(data)),
+		e => panic!("unknown encoding {e}"),
+	};
+
+	let mut out = String::new();
+	reader.read_to_string(&mut out).await.unwrap();
+	out
+}
+
+async fn request(
+	addr: std::net::SocketAddr,
+	uri: &str,
+	accept_encoding: &str,
+) -> util::TestResponse {
+	make_request!("GET", addr, uri, |builder| {
+		builder
+			.header("accept-encoding", accept_encoding)
+			.body(Body::new().into_http_body())
+			.unwrap()
+	})
+	.await
+}
+
+#[tokio::test]
+async fn compress_responses() {
+	let addr = spawn_server!(|builder| {
+		builder.add_middleware(Compression::new());
+		builder.add_route(large_text);
+		builder.add_route(small);
+		builder.add_route(image);
+		builder.add_route(stream);
+		builder.add_route(etag);
+	});
+
+	for (accept, expected) in [
+		("gzip", "gzip"),
+		("gzip, deflate, br, zstd", "br"),
+		("br;q=0.5, zstd;q=0.8, gzip;q=0.1", "zstd"),
+		("br;q=0, *", "zstd"),
+	] {
+		let mut resp = request(addr, "/text", accept)
+			.await
+			.assert_status(200)
+			.assert_header("content-encoding", expected)
+			.assert_header("vary", "accept-encoding")
+			.assert_not_header("content-length");
+
+		let body = resp.take_body().into_bytes().await.unwrap();
+		assert_eq!(decode(expected, body.to_vec()).await, text());
+	}
+
+	// streaming bodies
+	let mut resp = request(addr, "/stream", "gzip")
+		.await
+		.assert_header("content-encoding", "gzip");
+	let body = resp.take_body().into_bytes().await.unwrap();
+	assert_eq!(decode("gzip", body.to_vec()).await, text());
+
+	// the client does not want a compressed response
+	request(addr, "/text", "identity")
+		.await
+		.assert_not_header("content-encoding")
+		.assert_header("vary", "accept-encoding")
+		.assert_body_str(&text())
+		.await;
+
+	request(addr, "/small", "gzip")
+		.await
+		.assert_not_header("content-encoding")
+		.assert_not_header("vary")
+		.assert_body_str("too small")
+		.await;
+
+	request(addr, "/image", "gzip")
+		.await
+		.assert_not_header("content-encoding")
+		.assert_header("content-length", "4096");
+
+	// the etag of a compressed response is weak
+	request(addr, "/etag", "gzip")
+		.await
+		.assert_header("content-encoding", "gzip")
+		.assert_header("etag", "W/\"abc\"");
+
+	request(addr, "/etag", "identity")
+		.await
+		.assert_header("etag", "\"abc\"");
+}
+
+#[test]
+fn negotiate() {
+	let compression = Compression::new();
+
+	assert_eq!(compression.negotiate(""), None);
+	assert_eq!(compression.negotiate("deflate"), None);
+	assert_eq!(compression.negotiate("gzip;q=0"), None);
+	assert_eq!(compression.negotiate("*"), Some(Encoding::Brotli));
+	assert_eq!(compression.negotiate("gzip;q=0.5, identity;q=0.9"), None);
+	assert_eq!(
+		compression.negotiate("GZIP;Q=0.5, br;q=0.4"),
+		Some(Encoding::Gzip)
+	);
+
+	let gzip_only = Compression::new().encodings(&[Encoding::Gzip]);
+	assert_eq!(gzip_only.negotiate("br, zstd"), None);
+	assert_eq!(gzip_only.negotiate("br, gzip;q=0.1"), Some(Encoding::Gzip));
+}
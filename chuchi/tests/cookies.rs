@@ -0,0 +1,81 @@
+use chuchi::get;
+use chuchi::header::{Cookie, Cookies, SameSite};
+use chuchi::Response;
+
+use std::time::Duration;
+
+#[macro_use]
+mod util;
+
+#[get("/read")]
+fn read(cookies: Cookies) -> String {
+	cookies
+		.iter()
+		.map(|(name, value)| format!("{name}={value}"))
+		.collect::<Vec<_>>()
+		.join(",")
+}
+
+#[get("/login")]
+fn login() -> Response {
+	Response::builder()
+		.cookie(
+			Cookie::builder("session", "a b")
+				.path("/")
+				.max_age(Duration::from_secs(3600))
+				.secure()
+				.http_only()
+				.same_site(SameSite::Strict)
+				.partitioned(),
+		)
+		.cookie(Cookie::new("theme", "dark"))
+		.cookie(Cookie::removal("old").path("/"))
+		.body("ok")
+		.build()
+}
+
+#[tokio::test]
+async fn read_cookies() {
+	let addr = spawn_server!(|builder| {
+		builder.add_route(read);
+	});
+
+	make_request!("GET", addr, "/read", |builder| {
+		builder
+			.header("cookie", "a=1; b=%F0%9F%9A%80")
+			.header("cookie", "c=\"3\"")
+			.body(chuchi::Body::new().into_http_body())
+			.unwrap()
+	})
+	.await
+	.assert_status(200)
+	.assert_body_str("a=1,b=🚀,c=3")
+	.await;
+
+	make_request!("GET", addr, "/read")
+		.await
+		.assert_status(200)
+		.assert_body_str("")
+		.await;
+}
+
+#[tokio::test]
+async fn set_cookies() {
+	let addr = spawn_server!(|builder| {
+		builder.add_route(login);
+	});
+
+	let resp = make_request!("GET", addr, "/login")
+		.await
+		.assert_status(200);
+
+	assert_eq!(
+		resp.headers("set-cookie"),
+		[
+			"session=a%20b; Path=/; Max-Age=3600; Secure; HttpOnly; \
+			SameSite=Strict; Partitioned",
+			"theme=dark",
+			"old=; Path=/; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+		]
+	);
+}
@@ -0,0 +1,168 @@
+use chuchi::cors::Cors;
+use chuchi::header::Method;
+use chuchi::{get, post, Body, Response};
+
+use std::time::Duration;
+
+#[macro_use]
+mod util;
+
+#[get("/")]
+fn hello() -> Response {
+	Response::builder()
+		.header("x-request-id", "42")
+		.body("Hello, World!")
+		.build()
+}
+
+#[post("/")]
+fn hello_post() -> &'static str {
+	"Hello, Post!"
+}
+
+async fn request(
+	addr: std::net::SocketAddr,
+	method: &str,
+	headers: &[(&str, &str)],
+) -> util::TestResponse {
+	make_request!(method, addr, "/", |builder| {
+		let builder = headers
+			.iter()
+			.fold(builder, |builder, (k, v)| builder.header(*k, *v));
+		builder.body(Body::new().into_http_body()).unwrap()
+	})
+	.await
+}
+
+#[tokio::test]
+async fn cors_origins() {
+	let addr = spawn_server!(|builder| {
+		builder.add_middleware(
+			Cors::new()
+				.allow_origins(["https://example.com", "https://chuchi.dev"])
+				.allow_methods([Method::GET, Method::POST, Method::PUT])
+				.allow_headers(["Content-Type"])
+				.expose_headers(["x-request-id"])
+				.allow_credentials()
+				.max_age(Duration::from_secs(600)),
+		);
+		builder.add_route(hello);
+		builder.add_route(hello_post);
+	});
+
+	// not a cors request
+	request(addr, "GET", &[])
+		.await
+		.assert_status(200)
+		.assert_not_header("access-control-allow-origin")
+		.assert_not_header("vary");
+
+	request(addr, "GET", &[("origin", "https://chuchi.dev")])
+		.await
+		.assert_status(200)
+		.assert_header("access-control-allow-origin", "https://chuchi.dev")
+		.assert_header("access-control-allow-credentials", "true")
+		.assert_header("access-control-expose-headers", "x-request-id")
+		.assert_header("vary", "origin")
+		.assert_body_str("Hello, World!")
+		.await;
+
+	// the browser blocks the response
+	request(addr, "GET", &[("origin", "https://evil.com")])
+		.await
+		.assert_status(200)
+		.assert_not_header("access-control-allow-origin")
+		.assert_not_header("access-control-allow-credentials")
+		.assert_header("vary", "origin");
+
+	// preflight
+	request(
+		addr,
+		"OPTIONS",
+		&[
+			("origin", "https://example.com"),
+			("access-control-request-method", "PUT"),
+			("access-control-request-headers", "content-type"),
+		],
+	)
+	.await
+	.assert_status(204)
+	.assert_header("access-control-allow-origin", "https://example.com")
+	.assert_header("access-control-allow-methods", "GET, POST, PUT")
+	.assert_header("access-control-allow-headers", "content-type")
+	.assert_header("access-control-allow-credentials", "true")
+	.assert_header("access-control-max-age", "600");
+
+	// disallowed preflights
+	for (origin, method, headers) in [
+		("https://evil.com", "GET", "content-type"),
+		("https://example.com", "DELETE", "content-type"),
+		("https://example.com", "POST", "content-type, authorization"),
+	] {
+		request(
+			addr,
+			"OPTIONS",
+			&[
+				("origin", origin),
+				("access-control-request-method", method),
+				("access-control-request-headers", headers),
+			],
+		)
+		.await
+		.assert_status(403)
+		.assert_not_header("access-control-allow-origin");
+	}
+}
+
+#[tokio::test]
+async fn cors_any_origin() {
+	let addr = spawn_server!(|builder| {
+		builder
+			.add_middleware(Cors::new().allow_any_origin().allow_any_header());
+		builder.add_route(hello);
+	});
+
+	request(addr, "GET", &[("origin", "https://example.com")])
+		.await
+		.assert_header("access-control-allow-origin", "*")
+		.assert_not_header("access-control-allow-credentials")
+		.assert_not_header("vary");
+
+	request(
+		addr,
+		"OPTIONS",
+		&[
+			("origin", "https://example.com"),
+			("access-control-request-method", "GET"),
+			("access-control-request-headers", "x-custom, authorization"),
+		],
+	)
+	.await
+	.assert_status(204)
+	.assert_header("access-control-allow-origin", "*")
+	.assert_header("access-control-allow-headers", "x-custom, authorization");
+}
+
+#[tokio::test]
+async fn cors_origin_fn() {
+	let addr = spawn_server!(|builder| {
+		builder.add_middleware(
+			Cors::new().allow_origin_fn(|o| o.ends_with(".chuchi.dev")),
+		);
+		builder.add_route(hello);
+	});
+
+	request(addr, "GET", &[("origin", "https://api.chuchi.dev")])
+		.await
+		.assert_header("access-control-allow-origin", "https://api.chuchi.dev");
+
+	request(addr, "GET", &[("origin", "https://chuchi.dev.evil.com")])
+		.await
+		.assert_not_header("access-control-allow-origin");
+}
+
+#[test]
+#[should_panic]
+fn credentials_with_any_origin() {
+	let _ = Cors::new().allow_any_origin().allow_credentials();
+}
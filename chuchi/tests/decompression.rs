@@ -0,0 +1,86 @@
+use chuchi::compression::Encoding;
+use chuchi::header::CONTENT_ENCODING;
+use chuchi::{post, Body, Request};
+
+#[macro_use]
+mod util;
+
+#[post("/")]
+async fn echo(req: &mut Request) -> chuchi::Result<String> {
+	// the header gets removed once the body is decompressed
+	let encoding = req
+		.header()
+		.value(CONTENT_ENCODING)
+		.unwrap_or("none")
+		.to_string();
+
+	let body = req
+		.take_body()
+		.into_string()
+		.await
+		.map_err(chuchi::Error::from_client_io)?;
+
+	Ok(format!("{encoding}: {body}"))
+}
+
+async fn request(
+	addr: std::net::SocketAddr,
+	encoding: &str,
+	body: Body,
+) -> util::TestResponse {
+	make_request!("POST", addr, "/", |builder| {
+		builder
+			.header("content-encoding", encoding)
+			.body(body.into_http_body())
+			.unwrap()
+	})
+	.await
+}
+
+#[tokio::test]
+async fn decompress_requests() {
+	let addr = spawn_server!(|builder| {
+		builder.request_size_limit(1024);
+		builder.request_decompression(true);
+		builder.add_route(echo);
+	});
+
+	for (name, encoding) in [("gzip", Encoding::Gzip), ("br", Encoding::Brotli)]
+	{
+		let body = encoding.encode(Body::from("Hello, World!"));
+		request(addr, name, body)
+			.await
+			.assert_status(200)
+			.assert_body_str("none: Hello, World!")
+			.await;
+	}
+
+	// unsupported encodings are passed on
+	request(addr, "zstd", Body::from("not compressed"))
+		.await
+		.assert_status(200)
+		.assert_body_str("zstd: not compressed")
+		.await;
+
+	// invalid data
+	request(addr, "gzip", Body::from("not compressed"))
+		.await
+		.assert_status(400);
+
+	// the size limit applies to the decompressed body
+	let bomb = Encoding::Gzip.encode(Body::from(vec![0u8; 1024 * 1024]));
+	request(addr, "gzip", bomb).await.assert_status(413);
+}
+
+#[tokio::test]
+async fn decompression_disabled() {
+	let addr = spawn_server!(|builder| {
+		builder.add_route(echo);
+	});
+
+	request(addr, "br", Body::from("Hello"))
+		.await
+		.assert_status(200)
+		.assert_body_str("br: Hello")
+		.await;
+}
@@ -201,3 +201,130 @@ async fn test_resource() {
 		.assert_body_vec(&data)
 		.await;
 }
+
+#[tokio::test]
+async fn test_method_not_allowed() {
+	#[get("/users/{id}")]
+	fn get_user(id: &PathStr) -> String {
+		format!("User: {}", id)
+	}
+
+	#[post("/users/{id}")]
+	fn post_user(id: &PathStr) -> String {
+		format!("Updated: {}", id)
+	}
+
+	let addr = spawn_server!(|builder| {
+		builder.add_route(get_user);
+		builder.add_route(post_user);
+	});
+
+	make_request!("DELETE", addr, "/users/1")
+		.await
+		.assert_status(405)
+		.assert_header("allow", "GET, POST, HEAD, OPTIONS");
+
+	make_request!("OPTIONS", addr, "/users/1")
+		.await
+		.assert_status(204)
+		.assert_header("allow", "GET, POST, HEAD, OPTIONS")
+		.assert_not_header("content-length");
+
+	// unknown paths are still not found
+	make_request!("DELETE", addr, "/groups/1")
+		.await
+		.assert_status(404)
+		.assert_not_header("allow");
+	make_request!("OPTIONS", addr, "/groups/1")
+		.await
+		.assert_status(404);
+}
+
+#[tokio::test]
+async fn test_explicit_options() {
+	#[get("/")]
+	fn get() -> &'static str {
+		"get"
+	}
+
+	struct Options;
+
+	impl chuchi::routes::Route for Options {
+		fn validate_requirements(
+			&self,
+			_params: &chuchi::routes::ParamsNames,
+			_resources: &Resources,
+		) {
+		}
+
+		fn path(&self) -> chuchi::routes::RoutePath {
+			chuchi::routes::RoutePath {
+				method: Some(chuchi::header::Method::OPTIONS),
+				path: "/".into(),
+			}
+		}
+
+		fn call<'a>(
+			&'a self,
+			_req: &'a mut Request,
+			_params: &'a chuchi::routes::PathParams,
+			_resources: &'a Resources,
+		) -> PinnedFuture<'a, chuchi::Result<Response>> {
+			PinnedFuture::new(async move { Ok(Response::text("options")) })
+		}
+	}
+
+	let addr = spawn_server!(|builder| {
+		builder.add_route(get);
+		builder.add_route(Options);
+	});
+
+	make_request!("OPTIONS", addr, "/")
+		.await
+		.assert_status(200)
+		.assert_body_str("options")
+		.await;
+
+	make_request!("PUT", addr, "/")
+		.await
+		.assert_status(405)
+		.assert_header("allow", "GET, HEAD, OPTIONS");
+}
+
+#[tokio::test]
+async fn test_head_fallback() {
+	const BODY: &str = "Hello, World!";
+
+	#[get("/")]
+	fn hello_world() -> &'static str {
+		BODY
+	}
+
+	#[get("/no-head", head = false)]
+	fn no_head() -> &'static str {
+		BODY
+	}
+
+	let addr = spawn_server!(|builder| {
+		builder.add_route(hello_world);
+		builder.add_route(no_head);
+	});
+
+	make_request!("HEAD", addr, "/")
+		.await
+		.assert_status(200)
+		.assert_header("content-type", "text/plain; charset=utf-8")
+		.assert_header("content-length", BODY.len().to_string())
+		.assert_body_str("")
+		.await;
+
+	make_request!("OPTIONS", addr, "/")
+		.await
+		.assert_status(204)
+		.assert_header("allow", "GET, HEAD, OPTIONS");
+
+	make_request!("HEAD", addr, "/no-head")
+		.await
+		.assert_status(405)
+		.assert_header("allow", "GET, OPTIONS");
+}
@@ -0,0 +1,79 @@
+use chuchi::extractor::Form;
+use chuchi::post;
+
+use serde::Deserialize;
+
+#[macro_use]
+mod util;
+
+#[derive(Debug, Deserialize)]
+struct Login {
+	username: String,
+	age: u8,
+	#[serde(default)]
+	roles: Vec<String>,
+}
+
+#[post("/login")]
+fn login(form: Form<Login>) -> String {
+	format!("{}:{}:{}", form.username, form.age, form.roles.join(","))
+}
+
+async fn post_form(
+	addr: std::net::SocketAddr,
+	content_type: &str,
+	body: &'static str,
+) -> util::TestResponse {
+	make_request!("POST", addr, "/login", |builder| {
+		builder
+			.header("content-type", content_type)
+			.body(chuchi::Body::into_http_body(body.into()))
+			.expect("request could not be built")
+	})
+	.await
+}
+
+#[tokio::test]
+async fn form_extractor() {
+	let addr = spawn_server!(|builder| {
+		builder.add_route(login);
+	});
+
+	post_form(
+		addr,
+		"application/x-www-form-urlencoded",
+		"username=J%C3%BCrg+M&age=30&roles=admin&roles=dev",
+	)
+	.await
+	.assert_status(200)
+	.assert_body_str("Jürg M:30:admin,dev")
+	.await;
+
+	post_form(
+		addr,
+		"application/x-www-form-urlencoded; charset=utf-8",
+		"username=a&age=1",
+	)
+	.await
+	.assert_status(200)
+	.assert_body_str("a:1:")
+	.await;
+
+	post_form(addr, "application/json", "username=a&age=1")
+		.await
+		.assert_status(415);
+
+	// invalid field
+	post_form(
+		addr,
+		"application/x-www-form-urlencoded",
+		"username=a&age=old",
+	)
+	.await
+	.assert_status(422);
+
+	// missing field
+	post_form(addr, "application/x-www-form-urlencoded", "age=1")
+		.await
+		.assert_status(422);
+}
@@ -1,4 +1,5 @@
-use chuchi::fs::StaticFiles;
+use chuchi::fs::{Etag, MemoryFile, Range, StaticFiles, StaticFilesOwned};
+use chuchi::memory_file;
 use chuchi::Body;
 
 #[macro_use]
@@ -42,3 +43,408 @@ async fn read_file() {
 	.assert_header("cache-control", "max-age=86400, public")
 	.assert_not_header("content-type");
 }
+
+async fn request(
+	addr: std::net::SocketAddr,
+	uri: &str,
+	headers: &[(&str, &str)],
+) -> util::TestResponse {
+	make_request!("GET", addr, uri, |builder| {
+		let builder = headers
+			.iter()
+			.fold(builder, |builder, (k, v)| builder.header(*k, *v));
+		builder.body(Body::new().into_http_body()).unwrap()
+	})
+	.await
+}
+
+#[tokio::test]
+async fn content_etag() {
+	const CSS: StaticFiles = StaticFiles::new("/css", "./examples/www/css");
+	// a second route behaves like a restarted server
+	const CSS2: StaticFiles = StaticFiles::new("/css2", "./examples/www/css");
+	const INDEX: MemoryFile =
+		memory_file!("/", "../examples/www/hello_world.html");
+
+	let addr = spawn_server!(|builder| {
+		builder.add_route(CSS);
+		builder.add_route(CSS2);
+		builder.add_route(INDEX);
+	});
+
+	let file_ctn = include_bytes!("./../examples/www/css/style.css");
+	let etag = Etag::from_content(file_ctn);
+
+	let res = request(addr, "/css/style.css", &[])
+		.await
+		.assert_status(200)
+		.assert_header("etag", etag.as_str());
+	let last_modified = res
+		.header("last-modified")
+		.expect("last-modified not found")
+		.to_string();
+
+	request(addr, "/css2/style.css", &[])
+		.await
+		.assert_header("etag", etag.as_str());
+
+	// if-none-match takes a list of etags and uses the weak comparison
+	let none_match = format!("\"other\", W/{etag}");
+	request(addr, "/css/style.css", &[("if-none-match", &none_match)])
+		.await
+		.assert_status(304)
+		.assert_header("etag", etag.as_str());
+
+	request(
+		addr,
+		"/css/style.css",
+		&[("if-modified-since", &last_modified)],
+	)
+	.await
+	.assert_status(304);
+
+	request(
+		addr,
+		"/css/style.css",
+		&[("if-modified-since", "Thu, 01 Jan 1970 00:00:00 GMT")],
+	)
+	.await
+	.assert_status(200);
+
+	request(addr, "/css/style.css", &[("if-match", etag.as_str())])
+		.await
+		.assert_status(200);
+
+	request(addr, "/css/style.css", &[("if-match", "\"other\"")])
+		.await
+		.assert_status(412);
+
+	// the range is only used if if-range matches
+	request(
+		addr,
+		"/css/style.css",
+		&[("range", "bytes=0-9"), ("if-range", etag.as_str())],
+	)
+	.await
+	.assert_status(206)
+	.assert_header("etag", etag.as_str())
+	.assert_body_vec(&file_ctn[..10])
+	.await;
+
+	request(
+		addr,
+		"/css/style.css",
+		&[("range", "bytes=0-9"), ("if-range", "\"other\"")],
+	)
+	.await
+	.assert_status(200)
+	.assert_body_vec(file_ctn)
+	.await;
+
+	request(
+		addr,
+		"/css/style.css",
+		&[("range", "bytes=0-9"), ("if-range", &last_modified)],
+	)
+	.await
+	.assert_status(206);
+
+	// memory files
+	let html = include_bytes!("./../examples/www/hello_world.html");
+	let etag = Etag::from_content(html);
+
+	request(addr, "/", &[])
+		.await
+		.assert_status(200)
+		.assert_header("etag", etag.as_str())
+		.assert_not_header("last-modified");
+
+	request(addr, "/", &[("if-none-match", etag.as_str())])
+		.await
+		.assert_status(304);
+}
+
+fn multipart_body(
+	boundary: &str,
+	content_type: &str,
+	ctn: &[u8],
+	ranges: &[(usize, usize)],
+) -> Vec<u8> {
+	let mut body = vec![];
+	for (i, (start, end)) in ranges.iter().enumerate() {
+		let sep = if i == 0 { "" } else { "\r\n" };
+		body.extend_from_slice(
+			format!(
+				"{sep}--{boundary}\r\nContent-Type: {content_type}\r\n\
+				Content-Range: bytes {start}-{end}/{}\r\n\r\n",
+				ctn.len()
+			)
+			.as_bytes(),
+		);
+		body.extend_from_slice(&ctn[*start..=*end]);
+	}
+	body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+	body
+}
+
+#[tokio::test]
+async fn ranges() {
+	const CSS: StaticFiles = StaticFiles::new("/css", "./examples/www/css");
+	const INDEX: MemoryFile =
+		memory_file!("/", "../examples/www/hello_world.html");
+
+	let addr = spawn_server!(|builder| {
+		builder.add_route(CSS);
+		builder.add_route(INDEX);
+	});
+
+	let css = include_bytes!("./../examples/www/css/style.css");
+	let html = include_bytes!("./../examples/www/hello_world.html");
+
+	for (uri, ctn, content_type) in [
+		("/css/style.css", &css[..], "text/css; charset=utf-8"),
+		("/", &html[..], "text/html; charset=utf-8"),
+	] {
+		let len = ctn.len();
+
+		// suffix range
+		request(addr, uri, &[("range", "bytes=-10")])
+			.await
+			.assert_status(206)
+			.assert_header("content-length", "10")
+			.assert_header(
+				"content-range",
+				format!("bytes {}-{}/{len}", len - 10, len - 1),
+			)
+			.assert_body_vec(&ctn[len - 10..])
+			.await;
+
+		// a single byte and an end after the end of the file
+		request(addr, uri, &[("range", "bytes=5-5")])
+			.await
+			.assert_status(206)
+			.assert_body_vec(&ctn[5..6])
+			.await;
+
+		request(addr, uri, &[("range", "bytes=2-99999999999999999999999")])
+			.await
+			.assert_status(206)
+			.assert_header(
+				"content-range",
+				format!("bytes 2-{}/{len}", len - 1),
+			)
+			.assert_body_vec(&ctn[2..])
+			.await;
+
+		// multiple ranges, the unsatisfiable one is left out
+		let res =
+			request(addr, uri, &[("range", "bytes=0-4, -3,99999-, 10-19")])
+				.await
+				.assert_status(206)
+				.assert_not_header("content-range");
+		let boundary = res
+			.header("content-type")
+			.and_then(|ct| ct.strip_prefix("multipart/byteranges; boundary="))
+			.expect("multipart content-type")
+			.to_string();
+		let body = multipart_body(
+			&boundary,
+			content_type,
+			ctn,
+			&[(0, 4), (len - 3, len - 1), (10, 19)],
+		);
+		res.assert_header("content-length", body.len().to_string())
+			.assert_body_vec(&body)
+			.await;
+
+		// nothing satisfiable
+		request(addr, uri, &[("range", "bytes=99999-")])
+			.await
+			.assert_status(416)
+			.assert_header("content-range", format!("bytes */{len}"))
+			.assert_not_header("etag");
+
+		// invalid ranges are ignored
+		for range in ["bytes=5-2", "bytes=a-", "items=0-5", "bytes=,"] {
+			request(addr, uri, &[("range", range)])
+				.await
+				.assert_status(200)
+				.assert_body_vec(ctn)
+				.await;
+		}
+	}
+}
+
+#[test]
+fn single_range() {
+	let range = Range::single(2, Some(5));
+	assert_eq!(range.as_single(), Some((2, Some(5))));
+	assert_eq!(range.resolve(4), [2..=3]);
+
+	assert_eq!(Range::single(2, None).resolve(10), [2..=9]);
+}
+
+#[tokio::test]
+async fn precompressed() {
+	let dir = std::env::temp_dir()
+		.join(format!("chuchi-precompressed-{}", std::process::id()));
+	std::fs::create_dir_all(&dir).unwrap();
+
+	let js = b"console.log('Hello, World!');";
+	std::fs::write(dir.join("app.js"), js).unwrap();
+	// the content does not matter since it is served as is
+	std::fs::write(dir.join("app.js.br"), b"brotli app.js").unwrap();
+	std::fs::write(dir.join("app.js.gz"), b"gzip app.js").unwrap();
+	std::fs::write(dir.join("plain.js"), js).unwrap();
+
+	let assets = dir.to_str().unwrap().to_string();
+	let addr = spawn_server!(|builder| {
+		builder.add_route(StaticFilesOwned::new("/assets".into(), assets));
+	});
+
+	let res = request(addr, "/assets/app.js", &[])
+		.await
+		.assert_status(200)
+		.assert_not_header("content-encoding")
+		.assert_header("vary", "accept-encoding");
+	let content_type = res.header("content-type").unwrap().to_string();
+	let etag = res.header("etag").unwrap().to_string();
+	res.assert_body_vec(js).await;
+
+	for (accept, encoding, body) in [
+		("gzip, deflate, br", "br", &b"brotli app.js"[..]),
+		("gzip", "gzip", b"gzip app.js"),
+		("br;q=0.5, gzip", "gzip", b"gzip app.js"),
+		("*", "br", b"brotli app.js"),
+	] {
+		let res =
+			request(addr, "/assets/app.js", &[("accept-encoding", accept)])
+				.await
+				.assert_status(200)
+				.assert_header("content-encoding", encoding)
+				.assert_header("content-type", &content_type)
+				.assert_header("content-length", body.len().to_string())
+				.assert_header("vary", "accept-encoding");
+		assert_ne!(res.header("etag").unwrap(), etag);
+		res.assert_body_vec(body).await;
+	}
+
+	for accept in ["identity;q=1, br;q=0.5", "br;q=0, gzip;q=0", "zstd"] {
+		request(addr, "/assets/app.js", &[("accept-encoding", accept)])
+			.await
+			.assert_status(200)
+			.assert_not_header("content-encoding")
+			.assert_header("vary", "accept-encoding")
+			.assert_body_vec(js)
+			.await;
+	}
+
+	// the etag of the precompressed file
+	let br_etag = request(addr, "/assets/app.js", &[("accept-encoding", "br")])
+		.await
+		.header("etag")
+		.unwrap()
+		.to_string();
+	request(
+		addr,
+		"/assets/app.js",
+		&[("accept-encoding", "br"), ("if-none-match", &br_etag)],
+	)
+	.await
+	.assert_status(304)
+	.assert_header("vary", "accept-encoding");
+
+	// ranges of the precompressed file
+	request(
+		addr,
+		"/assets/app.js",
+		&[("accept-encoding", "br"), ("range", "bytes=0-5")],
+	)
+	.await
+	.assert_status(206)
+	.assert_header("content-encoding", "br")
+	.assert_header("content-range", "bytes 0-5/13")
+	.assert_body_vec(b"brotli")
+	.await;
+
+	// multiple ranges use the original file
+	request(
+		addr,
+		"/assets/app.js",
+		&[("accept-encoding", "br"), ("range", "bytes=0-1, 3-4")],
+	)
+	.await
+	.assert_status(206)
+	.assert_not_header("content-encoding")
+	.assert_header("vary", "accept-encoding");
+
+	// without siblings the response does not vary
+	request(addr, "/assets/plain.js", &[("accept-encoding", "br")])
+		.await
+		.assert_status(200)
+		.assert_not_header("content-encoding")
+		.assert_not_header("vary")
+		.assert_body_vec(js)
+		.await;
+
+	std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn single_page_app() {
+	const APP: StaticFiles =
+		StaticFiles::spa("/", "./examples/www", "hello_world.html");
+
+	let addr = spawn_server!(|builder| {
+		builder.add_route(APP);
+		builder.add_route(StaticFilesOwned::spa(
+			"/admin/".into(),
+			"./examples/www".into(),
+			"hello_world.html".into(),
+		));
+	});
+
+	let html = include_bytes!("./../examples/www/hello_world.html");
+	let css = include_bytes!("./../examples/www/css/style.css");
+
+	// routes of the app get the index
+	for uri in ["/", "/users/1", "/css", "/admin", "/admin/", "/admin/users"] {
+		request(addr, uri, &[])
+			.await
+			.assert_status(200)
+			.assert_header("content-type", "text/html; charset=utf-8")
+			.assert_header("cache-control", "no-cache")
+			.assert_body_vec(html)
+			.await;
+	}
+
+	// existing files are served as usual
+	request(addr, "/css/style.css", &[])
+		.await
+		.assert_status(200)
+		.assert_header("cache-control", "max-age=86400, public")
+		.assert_body_vec(css)
+		.await;
+
+	request(addr, "/admin/css/style.css", &[])
+		.await
+		.assert_status(200)
+		.assert_body_vec(css)
+		.await;
+
+	// missing assets are not found
+	request(addr, "/css/missing.css", &[])
+		.await
+		.assert_status(404);
+
+	request(addr, "/admin/missing.js", &[])
+		.await
+		.assert_status(404);
+
+	// the index can still be revalidated
+	let etag = Etag::from_content(html);
+	request(addr, "/users/1", &[("if-none-match", etag.as_str())])
+		.await
+		.assert_status(304)
+		.assert_header("cache-control", "no-cache");
+}
@@ -0,0 +1,180 @@
+use chuchi::extractor::PathStr;
+use chuchi::header::{RequestHeader, ResponseHeader, StatusCode};
+use chuchi::resources::Resources;
+use chuchi::routes::{Catcher, Scope};
+use chuchi::util::PinnedFuture;
+use chuchi::{get, post, Body, Request, Response};
+
+use std::net::SocketAddr;
+
+#[macro_use]
+mod util;
+
+async fn request(
+	method: &str,
+	addr: SocketAddr,
+	host: &'static str,
+	uri: &str,
+) -> util::TestResponse {
+	make_request!(method, addr, uri, |builder| {
+		let mut req = builder.body(Body::new().into_http_body()).unwrap();
+		req.headers_mut()
+			.insert("host", host.parse().expect("invalid host"));
+		req
+	})
+	.await
+}
+
+#[get("/", host = "admin.example.com")]
+fn admin() -> &'static str {
+	"admin"
+}
+
+#[get("/", host = "*.example.com")]
+fn wildcard() -> &'static str {
+	"wildcard"
+}
+
+#[get("/", host = "{tenant}.example.com")]
+fn tenant_root(tenant: &PathStr) -> String {
+	format!("tenant {tenant}")
+}
+
+#[get("/")]
+fn any_host() -> &'static str {
+	"any"
+}
+
+#[tokio::test]
+async fn host_patterns() {
+	let addr = spawn_server!(|builder| {
+		builder.add_route(any_host);
+		builder.add_route(wildcard);
+		builder.add_route(tenant_root);
+		builder.add_route(admin);
+	});
+
+	// exact patterns are tried first
+	request("GET", addr, "Admin.Example.com:8080", "/")
+		.await
+		.assert_status(200)
+		.assert_body_str("admin")
+		.await;
+
+	// then patterns with params
+	request("GET", addr, "acme.example.com", "/")
+		.await
+		.assert_status(200)
+		.assert_body_str("tenant acme")
+		.await;
+
+	request("GET", addr, "a.b.example.com", "/")
+		.await
+		.assert_status(200)
+		.assert_body_str("wildcard")
+		.await;
+
+	// routes without a host match every host
+	request("GET", addr, "example.com", "/")
+		.await
+		.assert_status(200)
+		.assert_body_str("any")
+		.await;
+}
+
+#[tokio::test]
+async fn host_falls_back_to_routes_without_host() {
+	#[get("/only-here")]
+	fn only_here() -> &'static str {
+		"only here"
+	}
+
+	#[post("/admin", host = "admin.example.com")]
+	fn admin_post() -> &'static str {
+		"posted"
+	}
+
+	let addr = spawn_server!(|builder| {
+		builder.add_route(admin_post);
+		builder.add_route(only_here);
+	});
+
+	request("GET", addr, "admin.example.com", "/only-here")
+		.await
+		.assert_status(200)
+		.assert_body_str("only here")
+		.await;
+
+	request("GET", addr, "admin.example.com", "/admin")
+		.await
+		.assert_status(405)
+		.assert_header("allow", "POST, OPTIONS");
+
+	request("POST", addr, "api.example.com", "/admin")
+		.await
+		.assert_status(404);
+}
+
+struct NotFound;
+
+impl Catcher for NotFound {
+	fn check(&self, _req: &RequestHeader, res: &ResponseHeader) -> bool {
+		res.status_code() == &StatusCode::NOT_FOUND
+	}
+
+	fn call<'a>(
+		&'a self,
+		_req: &'a mut Request,
+		resp: &'a mut Response,
+		_data: &'a Resources,
+	) -> PinnedFuture<'a, chuchi::Result<()>> {
+		PinnedFuture::new(async move {
+			*resp = Response::builder()
+				.status_code(StatusCode::NOT_FOUND)
+				.body("tenant not found")
+				.build();
+			Ok(())
+		})
+	}
+}
+
+#[tokio::test]
+async fn scope_host() {
+	#[get("/users/{id}")]
+	fn user(tenant: &PathStr, id: &PathStr) -> String {
+		format!("{tenant}:{id}")
+	}
+
+	let addr = spawn_server!(|builder| {
+		let mut scope = Scope::new();
+		scope.host("{tenant}.example.com");
+		scope.add_route(user);
+		scope.add_catcher(NotFound);
+
+		builder.mount("/api", scope);
+	});
+
+	request("GET", addr, "acme.example.com", "/api/users/1")
+		.await
+		.assert_status(200)
+		.assert_body_str("acme:1")
+		.await;
+
+	request("GET", addr, "acme.example.com", "/api/other")
+		.await
+		.assert_status(404)
+		.assert_body_str("tenant not found")
+		.await;
+
+	request("GET", addr, "example.com", "/api/users/1")
+		.await
+		.assert_status(404)
+		.assert_body_str("")
+		.await;
+}
+
+#[test]
+#[should_panic(expected = "invalid host pattern")]
+fn invalid_pattern() {
+	Scope::new().host("admin.*.com");
+}
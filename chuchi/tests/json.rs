@@ -77,3 +77,54 @@ async fn write_json() {
 		.assert_body_str(body)
 		.await;
 }
+
+#[tokio::test]
+async fn json_extractor() {
+	#[chuchi::post("/")]
+	fn echo(
+		data: chuchi::json::Json<JsonData>,
+	) -> chuchi::json::Json<JsonData> {
+		data
+	}
+
+	let addr = spawn_server!(|builder| {
+		builder.request_size_limit(100);
+		builder.add_route(echo);
+	});
+
+	let body = "{\"number\":10,\"yes\":false,\"comment\":\"Hello, World!\"}";
+
+	let req = |content_type: &'static str, body: &'static str| async move {
+		make_request!("POST", addr, "/", |builder| {
+			builder
+				.header("content-type", content_type)
+				.body(chuchi::Body::into_http_body(body.into()))
+				.expect("request could not be built")
+		})
+		.await
+	};
+
+	req("application/json; charset=utf-8", body)
+		.await
+		.assert_status(200)
+		.assert_header("content-type", "application/json; charset=utf-8")
+		.assert_body_str(body)
+		.await;
+
+	req("text/plain", body).await.assert_status(415);
+
+	// invalid json
+	req("application/json", "{\"number\":")
+		.await
+		.assert_status(400);
+	let trailing = format!("{body} {{}}").leak();
+	req("application/json", trailing).await.assert_status(400);
+
+	// valid json but wrong types
+	req("application/json", "{\"number\":\"10\"}")
+		.await
+		.assert_status(422);
+
+	let too_big = format!("{{\"comment\":\"{}\"}}", "a".repeat(200)).leak();
+	req("application/json", too_big).await.assert_status(413);
+}
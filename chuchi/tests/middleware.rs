@@ -0,0 +1,220 @@
+use chuchi::header::{Method, StatusCode};
+use chuchi::resources::Resources;
+use chuchi::routes::{Middleware, Next};
+use chuchi::util::PinnedFuture;
+use chuchi::{get, Request, Response};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[macro_use]
+mod util;
+
+struct Auth;
+
+impl Middleware for Auth {
+	fn call<'a>(
+		&'a self,
+		req: &'a mut Request,
+		_resources: &'a Resources,
+		next: Next<'a>,
+	) -> PinnedFuture<'a, chuchi::Result<Response>> {
+		PinnedFuture::new(async move {
+			if req.header().value("authorization") != Some("secret") {
+				return Ok(StatusCode::UNAUTHORIZED.into());
+			}
+
+			next.run(req).await
+		})
+	}
+}
+
+struct AddHeader(&'static str);
+
+impl Middleware for AddHeader {
+	fn call<'a>(
+		&'a self,
+		req: &'a mut Request,
+		_resources: &'a Resources,
+		next: Next<'a>,
+	) -> PinnedFuture<'a, chuchi::Result<Response>> {
+		PinnedFuture::new(async move {
+			let mut resp = next.run(req).await?;
+
+			let order = match resp.header().value("x-order") {
+				Some(order) => format!("{order},{}", self.0),
+				None => self.0.to_string(),
+			};
+			resp.header.values.insert("x-order", order);
+
+			Ok(resp)
+		})
+	}
+}
+
+#[tokio::test]
+async fn short_circuit_and_opt_out() {
+	#[get("/private")]
+	fn private() -> &'static str {
+		"private"
+	}
+
+	#[get("/health", skip_middleware = [Auth])]
+	fn health() -> &'static str {
+		"ok"
+	}
+
+	let addr = spawn_server!(|builder| {
+		builder.add_middleware(Auth);
+		builder.add_route(private);
+		builder.add_route(health);
+	});
+
+	make_request!("GET", addr, "/private")
+		.await
+		.assert_status(401);
+
+	make_request!("GET", addr, "/private", |builder| {
+		builder
+			.header("authorization", "secret")
+			.body(chuchi::Body::new().into_http_body())
+			.unwrap()
+	})
+	.await
+	.assert_status(200)
+	.assert_body_str("private")
+	.await;
+
+	make_request!("GET", addr, "/health")
+		.await
+		.assert_status(200)
+		.assert_body_str("ok")
+		.await;
+}
+
+#[tokio::test]
+async fn ordering() {
+	#[get("/")]
+	fn hello() -> &'static str {
+		"hello"
+	}
+
+	let addr = spawn_server!(|builder| {
+		builder.add_middleware(AddHeader("outer"));
+		builder.add_middleware(AddHeader("inner"));
+		builder.add_route(hello);
+	});
+
+	// the inner middleware sees the response first
+	make_request!("GET", addr, "/")
+		.await
+		.assert_status(200)
+		.assert_header("x-order", "inner,outer");
+
+	// middlewares also wrap requests without a route
+	make_request!("GET", addr, "/not-found")
+		.await
+		.assert_status(404)
+		.assert_header("x-order", "inner,outer");
+}
+
+#[tokio::test]
+async fn rewrite_with_resources() {
+	struct Counter(AtomicUsize);
+
+	struct Rewrite;
+
+	impl Middleware for Rewrite {
+		fn validate_requirements(&self, resources: &Resources) {
+			assert!(resources.exists::<Counter>());
+		}
+
+		fn call<'a>(
+			&'a self,
+			req: &'a mut Request,
+			resources: &'a Resources,
+			next: Next<'a>,
+		) -> PinnedFuture<'a, chuchi::Result<Response>> {
+			PinnedFuture::new(async move {
+				let counter = resources.get::<Counter>().unwrap();
+				let count = counter.0.fetch_add(1, Ordering::Relaxed) + 1;
+
+				if req.header().uri().path() == "/old" {
+					req.header.uri = "/new".parse().unwrap();
+				}
+
+				let mut resp = next.run(req).await?;
+				resp.header.values.insert("x-count", count.to_string());
+
+				Ok(resp)
+			})
+		}
+	}
+
+	#[get("/new")]
+	fn new() -> &'static str {
+		"new"
+	}
+
+	let addr = spawn_server!(|builder| {
+		builder.add_resource(Counter(AtomicUsize::new(0)));
+		builder.add_middleware(Rewrite);
+		builder.add_route(new);
+	});
+
+	make_request!("GET", addr, "/old")
+		.await
+		.assert_status(200)
+		.assert_header("x-count", "1")
+		.assert_body_str("new")
+		.await;
+
+	make_request!("GET", addr, "/new")
+		.await
+		.assert_status(200)
+		.assert_header("x-count", "2");
+}
+
+#[tokio::test]
+async fn shared_route() {
+	#[get("/private")]
+	fn private() -> &'static str {
+		"private"
+	}
+
+	let mut server = chuchi::build("127.0.0.1:0").await.unwrap();
+	server.add_middleware(Auth);
+	server.add_route(private);
+	let shared = server.into_shared();
+
+	let request = |method: Method, uri: &str| {
+		Request::builder(uri.parse().unwrap())
+			.method(method)
+			.header("authorization", "secret")
+			.build()
+	};
+
+	// middlewares are called
+	let mut req = Request::builder("/private".parse().unwrap()).build();
+	let resp = shared.route(&mut req).await.unwrap().unwrap();
+	assert_eq!(resp.header.status_code, StatusCode::UNAUTHORIZED);
+
+	let mut req = request(Method::GET, "/private");
+	let resp = shared.route(&mut req).await.unwrap().unwrap();
+	assert_eq!(resp.header.status_code, StatusCode::OK);
+	assert_eq!(resp.body.into_string().await.unwrap(), "private");
+
+	// the body of a HEAD response is removed
+	let mut req = request(Method::HEAD, "/private");
+	let resp = shared.route(&mut req).await.unwrap().unwrap();
+	assert_eq!(resp.header.status_code, StatusCode::OK);
+	assert_eq!(resp.header.value("content-length"), Some("7"));
+	assert!(resp.body.is_empty());
+
+	let mut req = request(Method::POST, "/private");
+	let resp = shared.route(&mut req).await.unwrap().unwrap();
+	assert_eq!(resp.header.status_code, StatusCode::METHOD_NOT_ALLOWED);
+	assert_eq!(resp.header.value("allow"), Some("GET, HEAD, OPTIONS"));
+
+	let mut req = request(Method::GET, "/not-found");
+	assert!(shared.route(&mut req).await.is_none());
+}
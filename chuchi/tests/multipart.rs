@@ -0,0 +1,129 @@
+use chuchi::extractor::Multipart;
+use chuchi::post;
+
+#[macro_use]
+mod util;
+
+const BOUNDARY: &str = "X-CHUCHI-BOUNDARY";
+
+fn multipart_body(parts: &[(&str, Option<&str>, &str)]) -> String {
+	let mut body = String::new();
+	for (name, file_name, data) in parts {
+		body.push_str(&format!("--{BOUNDARY}\r\n"));
+		match file_name {
+			Some(file_name) => body.push_str(&format!(
+				"Content-Disposition: form-data; name=\"{name}\"; \
+				filename=\"{file_name}\"\r\n\
+				Content-Type: text/plain\r\n"
+			)),
+			None => body.push_str(&format!(
+				"Content-Disposition: form-data; name=\"{name}\"\r\n"
+			)),
+		}
+		body.push_str(&format!("\r\n{data}\r\n"));
+	}
+	body.push_str(&format!("--{BOUNDARY}--\r\n"));
+
+	body
+}
+
+#[post("/upload")]
+async fn upload(mut multipart: Multipart) -> chuchi::Result<String> {
+	multipart.set_part_size_limit(Some(10));
+
+	let mut out = vec![];
+	while let Some(part) = multipart.next_part().await? {
+		let name = part.name().unwrap_or("").to_string();
+		let file_name = part.file_name().map(ToString::to_string);
+		let content_type = part.content_type().map(ToString::to_string);
+		let text = part.text().await?;
+
+		out.push(format!("{name}:{file_name:?}:{content_type:?}:{text}"));
+	}
+
+	Ok(out.join("|"))
+}
+
+async fn post_multipart(
+	addr: std::net::SocketAddr,
+	content_type: &str,
+	body: String,
+) -> util::TestResponse {
+	make_request!("POST", addr, "/upload", |builder| {
+		builder
+			.header("content-type", content_type)
+			.body(chuchi::Body::into_http_body(body.into()))
+			.expect("request could not be built")
+	})
+	.await
+}
+
+#[tokio::test]
+async fn multipart_extractor() {
+	let addr = spawn_server!(|builder| {
+		builder.request_size_limit(1024);
+		builder.add_route(upload);
+	});
+
+	let content_type = format!("multipart/form-data; boundary={BOUNDARY}");
+
+	let body = multipart_body(&[
+		("title", None, "hello"),
+		("file", Some("a.txt"), "content"),
+	]);
+	post_multipart(addr, &content_type, body)
+		.await
+		.assert_status(200)
+		.assert_body_str(
+			"title:None:None:hello|file:Some(\"a.txt\"):Some(\"text/plain\"):content",
+		)
+		.await;
+
+	// wrong content-type
+	let body = multipart_body(&[("title", None, "hello")]);
+	post_multipart(addr, "text/plain", body)
+		.await
+		.assert_status(415);
+
+	// part too large
+	let body = multipart_body(&[("title", None, "more than ten bytes")]);
+	post_multipart(addr, &content_type, body)
+		.await
+		.assert_status(413);
+
+	// whole body too large
+	let data = "a".repeat(2000);
+	let body = multipart_body(&[("title", None, &data)]);
+	post_multipart(addr, &content_type, body)
+		.await
+		.assert_status(413);
+}
+
+#[cfg(feature = "fs")]
+#[tokio::test]
+async fn temp_file() {
+	#[post("/upload")]
+	async fn upload_file(mut multipart: Multipart) -> chuchi::Result<String> {
+		let part = multipart.next_part().await?.unwrap();
+		let file = part.into_temp_file().await?;
+
+		let content = std::fs::read_to_string(file.path()).unwrap();
+		let path = file.path().to_path_buf();
+		drop(file);
+		assert!(!path.exists());
+
+		Ok(content)
+	}
+
+	let addr = spawn_server!(|builder| {
+		builder.add_route(upload_file);
+	});
+
+	let content_type = format!("multipart/form-data; boundary={BOUNDARY}");
+	let body = multipart_body(&[("file", Some("a.txt"), "file content")]);
+	post_multipart(addr, &content_type, body)
+		.await
+		.assert_status(200)
+		.assert_body_str("file content")
+		.await;
+}
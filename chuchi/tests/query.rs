@@ -0,0 +1,91 @@
+use chuchi::extractor::Query;
+use chuchi::get;
+
+use serde::Deserialize;
+
+#[macro_use]
+mod util;
+
+#[derive(Debug, Deserialize)]
+struct Search {
+	q: String,
+	page: Option<u32>,
+}
+
+#[get("/search")]
+fn search(query: Query<Search>) -> String {
+	format!("{}:{}", query.q, query.page.unwrap_or(1))
+}
+
+#[get("/optional")]
+fn optional(query: Option<Query<Search>>) -> String {
+	match query {
+		Some(s) => s.q.clone(),
+		None => "none".into(),
+	}
+}
+
+#[tokio::test]
+async fn query_extractor() {
+	let addr = spawn_server!(|builder| {
+		builder.add_route(search);
+		builder.add_route(optional);
+	});
+
+	make_request!("GET", addr, "/search?q=hello%20world&page=2")
+		.await
+		.assert_status(200)
+		.assert_body_str("hello world:2")
+		.await;
+
+	make_request!("GET", addr, "/search?q=a")
+		.await
+		.assert_status(200)
+		.assert_body_str("a:1")
+		.await;
+
+	// missing field
+	make_request!("GET", addr, "/search")
+		.await
+		.assert_status(400);
+
+	// invalid field
+	make_request!("GET", addr, "/search?q=a&page=b")
+		.await
+		.assert_status(400);
+
+	make_request!("GET", addr, "/optional")
+		.await
+		.assert_status(200)
+		.assert_body_str("none")
+		.await;
+
+	make_request!("GET", addr, "/optional?q=b")
+		.await
+		.assert_status(200)
+		.assert_body_str("b")
+		.await;
+
+	make_request!("GET", addr, "/optional?page=1")
+		.await
+		.assert_status(400);
+}
+
+#[test]
+fn error_contains_path() {
+	#[derive(Debug, Deserialize)]
+	#[allow(dead_code)]
+	struct Page {
+		page: u32,
+	}
+
+	let header = chuchi::header::RequestHeader {
+		address: ([127, 0, 0, 1], 0).into(),
+		method: chuchi::header::Method::GET,
+		uri: "http://localhost/?page=abc".parse().unwrap(),
+		values: Default::default(),
+	};
+
+	let err = Query::<Page>::from_header(&header).unwrap_err();
+	assert_eq!(err.path(), "page");
+}
@@ -0,0 +1,58 @@
+use chuchi::extractor::PathStr;
+use chuchi::fs::StaticFiles;
+use chuchi::header::Method;
+use chuchi::routes::{RouteKind, Scope};
+use chuchi::ws::WebSocket;
+use chuchi::{get, post, ws, Chuchi};
+
+#[get("/users/{id}", name = "user")]
+fn user(id: &PathStr) -> String {
+	id.to_string()
+}
+
+#[post("/files/{*?rest}", host = "{tenant}.example.com")]
+fn upload(tenant: &PathStr, rest: &PathStr) -> String {
+	format!("{tenant}{rest}")
+}
+
+#[ws("/ws")]
+async fn websocket(_ws: WebSocket) {}
+
+#[test]
+fn routes_info() {
+	let mut server = Chuchi::new_localhost();
+	server.add_route(user);
+	server.add_raw_route(websocket);
+	server.add_route(StaticFiles::new("/assets", "./assets"));
+
+	let mut scope = Scope::new();
+	scope.add_route(upload);
+	server.mount("/api", scope);
+
+	let info = server.routes_info();
+	assert_eq!(info.len(), 4);
+
+	assert_eq!(info[0].method, Some(Method::GET));
+	assert_eq!(info[0].path, "/users/{id}");
+	assert_eq!(info[0].params, ["id"]);
+	assert_eq!(info[0].kind, RouteKind::Basic);
+	assert_eq!(info[0].name.as_deref(), Some("user"));
+	assert!(info[0].type_name.ends_with("::user"));
+
+	assert_eq!(info[1].path, "/ws");
+	assert_eq!(info[1].kind, RouteKind::Ws);
+
+	assert_eq!(info[2].path, "/assets/{*rem}");
+	assert_eq!(info[2].kind, RouteKind::StaticFiles);
+
+	// mounted routes show the full path and the original type
+	assert_eq!(info[3].method, Some(Method::POST));
+	assert_eq!(info[3].path, "/api/files/{*?rest}");
+	assert_eq!(info[3].host.as_deref(), Some("{tenant}.example.com"));
+	assert_eq!(info[3].params, ["tenant", "rest"]);
+	assert_eq!(info[3].kind, RouteKind::Basic);
+	assert!(info[3].type_name.ends_with("::upload"));
+
+	let shared = server.into_shared();
+	assert_eq!(shared.routes_info().len(), 4);
+}
@@ -0,0 +1,228 @@
+use chuchi::extractor::PathStr;
+use chuchi::header::{RequestHeader, ResponseHeader, StatusCode};
+use chuchi::resources::Resources;
+use chuchi::routes::{Catcher, Middleware, Next, Scope};
+use chuchi::util::PinnedFuture;
+use chuchi::{get, Request, Response};
+
+#[macro_use]
+mod util;
+
+struct AddHeader(&'static str);
+
+impl Middleware for AddHeader {
+	fn call<'a>(
+		&'a self,
+		req: &'a mut Request,
+		_resources: &'a Resources,
+		next: Next<'a>,
+	) -> PinnedFuture<'a, chuchi::Result<Response>> {
+		PinnedFuture::new(async move {
+			let mut resp = next.run(req).await?;
+
+			let order = match resp.header().value("x-order") {
+				Some(order) => format!("{order},{}", self.0),
+				None => self.0.to_string(),
+			};
+			resp.header.values.insert("x-order", order);
+
+			Ok(resp)
+		})
+	}
+}
+
+struct NotFound(&'static str);
+
+impl Catcher for NotFound {
+	fn check(&self, _req: &RequestHeader, res: &ResponseHeader) -> bool {
+		res.status_code() == &StatusCode::NOT_FOUND
+	}
+
+	fn call<'a>(
+		&'a self,
+		_req: &'a mut Request,
+		resp: &'a mut Response,
+		_data: &'a Resources,
+	) -> PinnedFuture<'a, chuchi::Result<()>> {
+		PinnedFuture::new(async move {
+			*resp = Response::builder()
+				.status_code(StatusCode::NOT_FOUND)
+				.body(self.0)
+				.build();
+
+			Ok(())
+		})
+	}
+}
+
+#[get("/")]
+fn index() -> &'static str {
+	"index"
+}
+
+#[get("/users/{id}")]
+fn user(id: &PathStr) -> String {
+	format!("user {id}")
+}
+
+#[tokio::test]
+async fn mount_with_prefix() {
+	let addr = spawn_server!(|builder| {
+		let mut api = Scope::new();
+		api.add_route(user);
+		api.add_route(index);
+
+		builder.mount("/api/v2", api);
+	});
+
+	make_request!("GET", addr, "/api/v2/users/1")
+		.await
+		.assert_status(200)
+		.assert_body_str("user 1")
+		.await;
+
+	make_request!("GET", addr, "/api/v2")
+		.await
+		.assert_status(200)
+		.assert_body_str("index")
+		.await;
+
+	make_request!("GET", addr, "/users/1")
+		.await
+		.assert_status(404);
+}
+
+#[tokio::test]
+async fn root_route_is_prefix() {
+	let addr = spawn_server!(|builder| {
+		let mut inner = Scope::new();
+		inner.add_route(index);
+
+		let mut api = Scope::new();
+		api.add_route(index);
+		api.mount("/inner/", inner);
+
+		builder.mount("/api", api);
+	});
+
+	make_request!("GET", addr, "/api")
+		.await
+		.assert_status(200)
+		.assert_body_str("index")
+		.await;
+
+	make_request!("GET", addr, "/api/inner")
+		.await
+		.assert_status(200)
+		.assert_body_str("index")
+		.await;
+
+	make_request!("GET", addr, "/api/").await.assert_status(404);
+}
+
+#[tokio::test]
+async fn middlewares_and_catchers() {
+	let addr = spawn_server!(|builder| {
+		let mut api = Scope::new();
+		api.add_middleware(AddHeader("scope"));
+		api.add_catcher(NotFound("api not found"));
+		api.add_route(user);
+
+		builder.add_middleware(AddHeader("global"));
+		builder.add_route(index);
+		builder.mount("/api", api);
+	});
+
+	// the global middleware wraps the middleware of the scope
+	make_request!("GET", addr, "/api/users/1")
+		.await
+		.assert_status(200)
+		.assert_header("x-order", "scope,global");
+
+	make_request!("GET", addr, "/")
+		.await
+		.assert_status(200)
+		.assert_header("x-order", "global");
+
+	make_request!("GET", addr, "/api/unknown")
+		.await
+		.assert_status(404)
+		.assert_body_str("api not found")
+		.await;
+
+	make_request!("GET", addr, "/apis")
+		.await
+		.assert_status(404)
+		.assert_body_str("")
+		.await;
+}
+
+#[tokio::test]
+async fn nested_scopes() {
+	#[get("/orgs/{org}/users/{id}")]
+	fn org_user(org: &PathStr, id: &PathStr) -> String {
+		format!("{org} {id}")
+	}
+
+	#[get("/members/{id}")]
+	fn member(org: &PathStr, id: &PathStr) -> String {
+		format!("member {org} {id}")
+	}
+
+	let addr = spawn_server!(|builder| {
+		let mut org = Scope::new();
+		org.add_middleware(AddHeader("inner"));
+		org.add_catcher(NotFound("org not found"));
+		org.add_route(member);
+
+		let mut api = Scope::new();
+		api.add_middleware(AddHeader("outer"));
+		api.add_route(org_user);
+		// the params of the prefix are available to the routes
+		api.mount("/orgs/{org}", org);
+
+		builder.mount("/api", api);
+	});
+
+	make_request!("GET", addr, "/api/orgs/a/users/1")
+		.await
+		.assert_status(200)
+		.assert_header("x-order", "outer")
+		.assert_body_str("a 1")
+		.await;
+
+	make_request!("GET", addr, "/api/orgs/a/members/1")
+		.await
+		.assert_status(200)
+		.assert_header("x-order", "inner,outer")
+		.assert_body_str("member a 1")
+		.await;
+
+	make_request!("GET", addr, "/api/orgs/a/unknown")
+		.await
+		.assert_status(404)
+		.assert_body_str("org not found")
+		.await;
+
+	make_request!("GET", addr, "/api/unknown")
+		.await
+		.assert_status(404)
+		.assert_body_str("")
+		.await;
+}
+
+#[tokio::test]
+#[should_panic]
+async fn missing_param_in_prefix() {
+	#[get("/members")]
+	fn members(org: &PathStr) -> String {
+		format!("{org}")
+	}
+
+	let mut builder = chuchi::Chuchi::new_localhost();
+
+	let mut scope = Scope::new();
+	scope.add_route(members);
+
+	builder.mount("/orgs", scope);
+}
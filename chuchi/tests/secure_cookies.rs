@@ -0,0 +1,138 @@
+use chuchi::cookie::{CookieKey, PrivateCookies, SignedCookies};
+use chuchi::get;
+use chuchi::header::Cookie;
+use chuchi::Response;
+
+#[macro_use]
+mod util;
+
+const SECRET: [u8; 32] = [1; 32];
+const OLD_SECRET: [u8; 32] = [2; 32];
+
+#[get("/signed")]
+fn signed_route(cookies: SignedCookies) -> Response {
+	let value = cookies.get("user").unwrap_or_else(|| "none".into());
+
+	Response::builder()
+		.cookie(cookies.sign(Cookie::new("user", "alice")))
+		.body(value)
+		.build()
+}
+
+#[get("/private")]
+fn private_route(cookies: PrivateCookies) -> Response {
+	let value = cookies.get("secret").unwrap_or_else(|| "none".into());
+
+	Response::builder()
+		.cookie(cookies.encrypt(Cookie::new("secret", "42")))
+		.body(value)
+		.build()
+}
+
+async fn request_with_cookie(
+	addr: std::net::SocketAddr,
+	uri: &str,
+	cookie: &str,
+) -> util::TestResponse {
+	make_request!("GET", addr, uri, |builder| {
+		builder
+			.header("cookie", cookie)
+			.body(chuchi::Body::new().into_http_body())
+			.unwrap()
+	})
+	.await
+}
+
+fn cookie_pair(resp: &util::TestResponse) -> String {
+	resp.header("set-cookie").unwrap().to_string()
+}
+
+#[tokio::test]
+async fn signed_cookies() {
+	let addr = spawn_server!(|builder| {
+		builder.add_resource(CookieKey::new(&SECRET));
+		builder.add_route(signed_route);
+	});
+
+	let resp = make_request!("GET", addr, "/signed")
+		.await
+		.assert_status(200);
+	let cookie = cookie_pair(&resp);
+	// the value is readable
+	assert!(cookie.starts_with("user=") && cookie.ends_with(".alice"));
+	resp.assert_body_str("none").await;
+
+	request_with_cookie(addr, "/signed", &cookie)
+		.await
+		.assert_body_str("alice")
+		.await;
+
+	// tampered value
+	let tampered = cookie.replace("alice", "bob");
+	request_with_cookie(addr, "/signed", &tampered)
+		.await
+		.assert_body_str("none")
+		.await;
+
+	// unsigned value
+	request_with_cookie(addr, "/signed", "user=alice")
+		.await
+		.assert_body_str("none")
+		.await;
+}
+
+#[tokio::test]
+async fn private_cookies() {
+	let addr = spawn_server!(|builder| {
+		builder.add_resource(CookieKey::new(&SECRET));
+		builder.add_route(private_route);
+	});
+
+	let resp = make_request!("GET", addr, "/private")
+		.await
+		.assert_status(200);
+	let cookie = cookie_pair(&resp);
+	assert!(cookie.starts_with("secret=") && !cookie.contains("42"));
+
+	request_with_cookie(addr, "/private", &cookie)
+		.await
+		.assert_body_str("42")
+		.await;
+
+	// the name is authenticated
+	let moved = cookie.replace("secret=", "other=");
+	let moved = format!("{moved}; secret=invalid");
+	request_with_cookie(addr, "/private", &moved)
+		.await
+		.assert_body_str("none")
+		.await;
+}
+
+#[test]
+fn key_rotation() {
+	let old = CookieKey::new(&OLD_SECRET);
+	let signed = old.sign("user", "alice");
+	let encrypted = old.encrypt("user", "alice");
+
+	let new = CookieKey::new(&SECRET);
+	assert_eq!(new.verify("user", &signed), None);
+	assert_eq!(new.decrypt("user", &encrypted), None);
+
+	let rotated = CookieKey::new(&SECRET).with_old_key(&OLD_SECRET);
+	assert_eq!(rotated.verify("user", &signed).as_deref(), Some("alice"));
+	assert_eq!(
+		rotated.decrypt("user", &encrypted).as_deref(),
+		Some("alice")
+	);
+
+	// new cookies use the newest key
+	let signed = rotated.sign("user", "alice");
+	assert_eq!(new.verify("user", &signed).as_deref(), Some("alice"));
+	assert_eq!(old.verify("user", &signed), None);
+}
+
+#[test]
+#[should_panic]
+fn short_key() {
+	CookieKey::new(&[0; 16]);
+}
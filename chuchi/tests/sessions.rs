@@ -0,0 +1,202 @@
+use chuchi::get;
+use chuchi::session::{
+	FileStore, MemoryStore, Session, SessionStore, Sessions,
+};
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+#[macro_use]
+mod util;
+
+#[get("/login")]
+fn login(session: Session) -> &'static str {
+	session.regenerate();
+	session.insert("user", "alice").unwrap();
+	"ok"
+}
+
+#[get("/user")]
+fn user(session: Session) -> String {
+	session.get("user").unwrap_or_else(|| "none".into())
+}
+
+#[get("/logout")]
+fn logout(session: Session) -> &'static str {
+	session.destroy();
+	"ok"
+}
+
+async fn get_with_cookie(
+	addr: SocketAddr,
+	uri: &str,
+	cookie: &str,
+) -> util::TestResponse {
+	make_request!("GET", addr, uri, |builder| {
+		builder
+			.header("cookie", cookie)
+			.body(chuchi::Body::new().into_http_body())
+			.unwrap()
+	})
+	.await
+}
+
+/// Returns the `name=value` part of the set-cookie header.
+fn session_cookie(resp: &util::TestResponse) -> String {
+	let header = resp.header("set-cookie").expect("no set-cookie header");
+	assert!(header.contains("HttpOnly") && header.contains("SameSite=Lax"));
+	header.split(';').next().unwrap().to_string()
+}
+
+async fn login_logout(addr: SocketAddr) {
+	// no data means no cookie
+	make_request!("GET", addr, "/user")
+		.await
+		.assert_not_header("set-cookie")
+		.assert_body_str("none")
+		.await;
+
+	let resp = make_request!("GET", addr, "/login")
+		.await
+		.assert_status(200);
+	let cookie = session_cookie(&resp);
+
+	get_with_cookie(addr, "/user", &cookie)
+		.await
+		.assert_not_header("set-cookie")
+		.assert_body_str("alice")
+		.await;
+
+	// the id changes after a login
+	let resp = get_with_cookie(addr, "/login", &cookie).await;
+	let new_cookie = session_cookie(&resp);
+	assert_ne!(cookie, new_cookie);
+
+	get_with_cookie(addr, "/user", &cookie)
+		.await
+		.assert_body_str("none")
+		.await;
+	get_with_cookie(addr, "/user", &new_cookie)
+		.await
+		.assert_body_str("alice")
+		.await;
+
+	let resp = get_with_cookie(addr, "/logout", &new_cookie).await;
+	assert!(resp.header("set-cookie").unwrap().contains("Max-Age=0"));
+
+	get_with_cookie(addr, "/user", &new_cookie)
+		.await
+		.assert_body_str("none")
+		.await;
+}
+
+#[tokio::test]
+async fn memory_sessions() {
+	let addr = spawn_server!(|builder| {
+		builder.add_middleware(Sessions::new(MemoryStore::new()));
+		builder.add_route(login);
+		builder.add_route(user);
+		builder.add_route(logout);
+	});
+
+	login_logout(addr).await;
+}
+
+#[tokio::test]
+async fn file_sessions() {
+	let dir = std::env::temp_dir()
+		.join(format!("chuchi-sessions-{}", std::process::id()));
+
+	let addr = spawn_server!(|builder| {
+		builder.add_middleware(Sessions::new(FileStore::new(&dir)));
+		builder.add_route(login);
+		builder.add_route(user);
+		builder.add_route(logout);
+	});
+
+	login_logout(addr).await;
+
+	// invalid ids cannot escape the directory
+	let store = FileStore::new(&dir);
+	assert!(store.load("../secret").await.unwrap().is_none());
+
+	// a corrupt session is treated as missing and removed
+	let corrupt = dir.join("corrupt.json");
+	std::fs::write(&corrupt, b"{\"data\":").unwrap();
+	assert!(store.load("corrupt").await.unwrap().is_none());
+	assert!(!corrupt.exists());
+
+	// no temporary files are left behind
+	let leftover = std::fs::read_dir(&dir)
+		.unwrap()
+		.filter_map(|e| e.ok())
+		.any(|e| e.file_name().to_string_lossy().ends_with(".tmp"));
+	assert!(!leftover);
+
+	std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn idle_timeout() {
+	let addr = spawn_server!(|builder| {
+		builder.add_middleware(
+			Sessions::new(MemoryStore::new())
+				.idle_timeout(Duration::from_millis(300)),
+		);
+		builder.add_route(login);
+		builder.add_route(user);
+	});
+
+	let resp = make_request!("GET", addr, "/login").await;
+	let cookie = session_cookie(&resp);
+
+	// using the session extends it
+	for _ in 0..3 {
+		tokio::time::sleep(Duration::from_millis(150)).await;
+		get_with_cookie(addr, "/user", &cookie)
+			.await
+			.assert_body_str("alice")
+			.await;
+	}
+
+	tokio::time::sleep(Duration::from_millis(400)).await;
+	get_with_cookie(addr, "/user", &cookie)
+		.await
+		.assert_body_str("none")
+		.await;
+}
+
+#[tokio::test]
+async fn absolute_timeout() {
+	let addr = spawn_server!(|builder| {
+		builder.add_middleware(
+			Sessions::new(MemoryStore::new())
+				.absolute_timeout(Duration::from_millis(300)),
+		);
+		builder.add_route(login);
+		builder.add_route(user);
+	});
+
+	let resp = make_request!("GET", addr, "/login").await;
+	let cookie = session_cookie(&resp);
+
+	get_with_cookie(addr, "/user", &cookie)
+		.await
+		.assert_body_str("alice")
+		.await;
+
+	tokio::time::sleep(Duration::from_millis(400)).await;
+	get_with_cookie(addr, "/user", &cookie)
+		.await
+		.assert_body_str("none")
+		.await;
+}
+
+#[tokio::test]
+async fn missing_middleware() {
+	let addr = spawn_server!(|builder| {
+		builder.add_route(user);
+	});
+
+	make_request!("GET", addr, "/user").await.assert_status(500);
+}
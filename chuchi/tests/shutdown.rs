@@ -0,0 +1,110 @@
+use chuchi::{get, ChuchiServer};
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+
+#[macro_use]
+mod util;
+
+#[get("/slow")]
+async fn slow() -> &'static str {
+	sleep(Duration::from_millis(300)).await;
+	"done"
+}
+
+#[get("/forever")]
+async fn forever() -> &'static str {
+	sleep(Duration::from_secs(60)).await;
+	"never"
+}
+
+async fn build_server(shutdown_timeout: Duration) -> ChuchiServer {
+	let socket_addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0);
+	let mut builder = chuchi::build(socket_addr).await.unwrap();
+	builder.shutdown_timeout(shutdown_timeout);
+	builder.add_route(slow);
+	builder.add_route(forever);
+	builder.build().await.unwrap()
+}
+
+#[tokio::test]
+async fn in_flight_request_finishes() {
+	let server = build_server(Duration::from_secs(5)).await;
+	let addr = server.local_addr().unwrap();
+
+	let (tx, rx) = oneshot::channel::<()>();
+	let server = tokio::spawn(server.run_with_shutdown(async {
+		let _ = rx.await;
+	}));
+
+	let req = tokio::spawn(async move {
+		make_request!("GET", addr, "/slow")
+			.await
+			.assert_status(200)
+			.assert_body_str("done")
+			.await;
+	});
+
+	// make sure the request reached the server
+	sleep(Duration::from_millis(100)).await;
+	tx.send(()).unwrap();
+
+	let shutdown = server.await.unwrap().unwrap();
+	req.await.unwrap();
+
+	assert_eq!(shutdown.graceful, 1);
+	assert_eq!(shutdown.force_closed, 0);
+
+	// no new connections are accepted
+	assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn drain_timeout_force_closes() {
+	let server = build_server(Duration::from_millis(100)).await;
+	let addr = server.local_addr().unwrap();
+
+	let (tx, rx) = oneshot::channel::<()>();
+	let server = tokio::spawn(server.run_with_shutdown(async {
+		let _ = rx.await;
+	}));
+
+	let req = tokio::spawn(async move {
+		util::send_request(
+			hyper::Request::builder()
+				.uri(format!("http://{addr}/forever"))
+				.header("host", addr.to_string())
+				.body(chuchi::Body::new().into_http_body())
+				.unwrap(),
+		)
+		.await
+	});
+
+	sleep(Duration::from_millis(100)).await;
+	tx.send(()).unwrap();
+
+	let shutdown = server.await.unwrap().unwrap();
+	assert_eq!(shutdown.graceful, 0);
+	assert_eq!(shutdown.force_closed, 1);
+
+	assert!(req.await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn run_task_with_shutdown() {
+	let socket_addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0);
+	let builder = chuchi::build(socket_addr).await.unwrap();
+
+	let task = builder.run_task_with_shutdown();
+	// give the server time to start
+	sleep(Duration::from_millis(50)).await;
+	assert!(!task.is_finished());
+
+	let shutdown = task.shutdown().await.unwrap();
+	assert_eq!(shutdown.graceful, 0);
+	assert_eq!(shutdown.force_closed, 0);
+}
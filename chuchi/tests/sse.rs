@@ -0,0 +1,103 @@
+use chuchi::get;
+use chuchi::sse::{Event, EventStream, LastEventId};
+
+use std::time::Duration;
+
+#[macro_use]
+mod util;
+
+#[get("/events")]
+fn events(last_id: LastEventId) -> EventStream {
+	let start: u32 = last_id
+		.as_str()
+		.and_then(|id| id.parse().ok())
+		.map_or(0, |id: u32| id + 1);
+
+	let (sender, stream) = EventStream::channel(4);
+
+	tokio::spawn(async move {
+		for i in start..3 {
+			let event = Event::new().id(i.to_string()).data(format!("{i}"));
+			sender.send(event).await.unwrap();
+		}
+	});
+
+	stream
+}
+
+#[get("/keep-alive")]
+fn keep_alive() -> EventStream {
+	let (sender, stream) = EventStream::channel(1);
+
+	tokio::spawn(async move {
+		tokio::time::sleep(Duration::from_millis(300)).await;
+		sender.send(Event::new().data("done")).await.unwrap();
+	});
+
+	stream.keep_alive(Some(Duration::from_millis(100)))
+}
+
+#[tokio::test]
+async fn event_stream() {
+	let addr = spawn_server!(|builder| {
+		builder.add_route(events);
+		builder.add_route(keep_alive);
+	});
+
+	make_request!("GET", addr, "/events")
+		.await
+		.assert_status(200)
+		.assert_header("content-type", "text/event-stream")
+		.assert_header("cache-control", "no-cache")
+		.assert_body_str(
+			"id: 0\ndata: 0\n\nid: 1\ndata: 1\n\nid: 2\ndata: 2\n\n",
+		)
+		.await;
+
+	// resume after the last event
+	make_request!("GET", addr, "/events", |builder| {
+		builder
+			.header("last-event-id", "1")
+			.body(chuchi::Body::new().into_http_body())
+			.unwrap()
+	})
+	.await
+	.assert_body_str("id: 2\ndata: 2\n\n")
+	.await;
+
+	let mut resp = make_request!("GET", addr, "/keep-alive").await;
+	let body = resp.take_body().into_string().await.unwrap();
+	assert!(body.starts_with(": keep-alive\n\n"), "{body}");
+	assert!(body.ends_with("data: done\n\n"), "{body}");
+}
+
+#[test]
+fn event_format() {
+	let event = Event::new()
+		.event("update")
+		.id("42")
+		.retry(Duration::from_secs(3))
+		.data("line 1\nline 2\r\nline 3\rline 4");
+
+	assert_eq!(
+		event.to_string(),
+		"event: update\nid: 42\nretry: 3000\ndata: line 1\ndata: line 2\n\
+		data: line 3\ndata: line 4\n\n"
+	);
+
+	assert_eq!(Event::new().data("").to_string(), "data: \n\n");
+	assert_eq!(Event::new().to_string(), "\n");
+}
+
+#[test]
+#[should_panic]
+fn event_id_with_line_break() {
+	let _ = Event::new().id("1\n2");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_event() {
+	let event = Event::new().json(&[1, 2, 3]).unwrap();
+	assert_eq!(event.to_string(), "data: [1,2,3]\n\n");
+}
@@ -0,0 +1,160 @@
+use chuchi::get;
+use chuchi::tls::{CertificateDer, PrivateKeyDer};
+use chuchi::Body;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::{PrivatePkcs8KeyDer, ServerName};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+use hyper_util::rt::TokioIo;
+
+#[macro_use]
+mod util;
+
+fn self_signed() -> (CertificateDer<'static>, PrivateKeyDer<'static>) {
+	let cert =
+		rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+
+	let key = PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der());
+
+	(cert.cert.der().clone(), key.into())
+}
+
+async fn connect(
+	addr: SocketAddr,
+	cert: CertificateDer<'static>,
+	alpn: &[&[u8]],
+) -> TlsStream<TcpStream> {
+	let mut roots = RootCertStore::empty();
+	roots.add(cert).unwrap();
+
+	let mut config =
+		ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+			.with_safe_default_protocol_versions()
+			.unwrap()
+			.with_root_certificates(roots)
+			.with_no_client_auth();
+	config.alpn_protocols = alpn.iter().map(|p| p.to_vec()).collect();
+
+	let stream = TcpStream::connect(addr).await.unwrap();
+
+	TlsConnector::from(Arc::new(config))
+		.connect(ServerName::try_from("localhost").unwrap(), stream)
+		.await
+		.expect("tls handshake failed")
+}
+
+#[tokio::test]
+async fn https_hello_world() {
+	const BODY: &str = "Hello, World!";
+
+	#[get("/")]
+	fn hello_world() -> &'static str {
+		BODY
+	}
+
+	let (cert, key) = self_signed();
+
+	let addr = spawn_server!(|builder| {
+		builder.tls(vec![cert.clone()], key).unwrap();
+		builder.add_route(hello_world);
+	});
+
+	let stream = connect(addr, cert, &[b"http/1.1"]).await;
+	assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"http/1.1"[..]));
+
+	let (mut sender, conn) =
+		hyper::client::conn::http1::handshake(TokioIo::new(stream))
+			.await
+			.unwrap();
+	tokio::task::spawn(conn);
+
+	let req = hyper::Request::builder()
+		.uri("/")
+		.header("host", "localhost")
+		.body(Body::new().into_http_body())
+		.unwrap();
+
+	let resp = sender.send_request(req).await.unwrap();
+	let resp = util::TestResponse::new(resp.map(Body::from_hyper));
+
+	resp.assert_status(200)
+		.assert_header("content-length", BODY.len().to_string())
+		.assert_body_str(BODY)
+		.await;
+}
+
+#[tokio::test]
+async fn alpn_negotiation() {
+	let (cert, key) = self_signed();
+
+	let addr = spawn_server!(|builder| {
+		builder.tls(vec![cert.clone()], key).unwrap();
+	});
+
+	let stream = connect(addr, cert, &[b"h2", b"http/1.1"]).await;
+	let protocol = stream.get_ref().1.alpn_protocol();
+
+	if cfg!(feature = "http2") {
+		assert_eq!(protocol, Some(&b"h2"[..]));
+	} else {
+		assert_eq!(protocol, Some(&b"http/1.1"[..]));
+	}
+}
+
+#[tokio::test]
+async fn plain_http_is_rejected() {
+	let (cert, key) = self_signed();
+
+	let addr = spawn_server!(|builder| {
+		builder.tls(vec![cert], key).unwrap();
+	});
+
+	let res = util::send_request(
+		hyper::Request::builder()
+			.uri(format!("http://{addr}/"))
+			.header("host", addr.to_string())
+			.body(Body::new().into_http_body())
+			.unwrap(),
+	)
+	.await;
+
+	assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn shutdown_during_handshake() {
+	let (cert, key) = self_signed();
+
+	let socket_addr = SocketAddr::from(([127, 0, 0, 1], 0));
+	let mut builder = chuchi::build(socket_addr).await.unwrap();
+	builder.tls(vec![cert], key).unwrap();
+	builder.shutdown_timeout(Duration::from_secs(10));
+	let server = builder.build().await.unwrap();
+	let addr = server.local_addr().unwrap();
+
+	let (tx, rx) = oneshot::channel::<()>();
+	let server = tokio::spawn(server.run_with_shutdown(async {
+		let _ = rx.await;
+	}));
+
+	// a client which never starts the handshake
+	let _stream = TcpStream::connect(addr).await.unwrap();
+	sleep(Duration::from_millis(50)).await;
+
+	let start = Instant::now();
+	tx.send(()).unwrap();
+	let shutdown = server.await.unwrap().unwrap();
+
+	assert!(start.elapsed() < Duration::from_secs(1));
+	assert_eq!(shutdown.force_closed, 0);
+}
@@ -0,0 +1,109 @@
+use chuchi::routes::HyperRequest;
+use chuchi::service::ChuchiService;
+use chuchi::tower::TowerRoute;
+use chuchi::{get, Body};
+
+use std::convert::Infallible;
+use std::net::{Ipv4Addr, SocketAddr};
+
+use tower::{service_fn, ServiceBuilder, ServiceExt};
+
+#[macro_use]
+mod util;
+
+#[get("/")]
+fn hello() -> &'static str {
+	"Hello, World!"
+}
+
+fn service() -> ChuchiService {
+	let mut builder = chuchi::Chuchi::new_localhost();
+	builder.add_route(hello);
+
+	let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0);
+	ChuchiService::new(builder.into_shared(), addr)
+}
+
+#[tokio::test]
+async fn chuchi_as_tower_service() {
+	let req = hyper::Request::builder()
+		.uri("/")
+		.header("host", "localhost")
+		.body(String::new())
+		.unwrap();
+
+	let resp = service().oneshot(req).await.unwrap();
+
+	util::TestResponse::new(resp.map(Body::from_http_body))
+		.assert_status(200)
+		.assert_body_str("Hello, World!")
+		.await;
+}
+
+#[tokio::test]
+async fn chuchi_with_tower_layer() {
+	let service = ServiceBuilder::new()
+		.map_response(|mut resp: hyper::Response<_>| {
+			resp.headers_mut()
+				.insert("x-layer", "applied".parse().unwrap());
+			resp
+		})
+		.service(service());
+
+	let req = hyper::Request::builder()
+		.uri("/")
+		.header("host", "localhost")
+		.body(String::new())
+		.unwrap();
+
+	let resp = service.oneshot(req).await.unwrap();
+
+	util::TestResponse::new(resp.map(Body::from_http_body))
+		.assert_status(200)
+		.assert_header("x-layer", "applied")
+		.assert_body_str("Hello, World!")
+		.await;
+}
+
+#[tokio::test]
+async fn tower_route() {
+	let echo = service_fn(|req: HyperRequest| async move {
+		let uri = req.uri().to_string();
+		let body = Body::from(req.into_body()).into_string().await.unwrap();
+
+		Ok::<_, Infallible>(
+			hyper::Response::builder()
+				.header("x-uri", uri)
+				.body(body)
+				.unwrap(),
+		)
+	});
+
+	let addr = spawn_server!(|builder| {
+		builder.add_route(hello);
+		builder.add_raw_route(TowerRoute::new("/tower", echo));
+	});
+
+	make_request!("GET", addr, "/tower/hello?a=b")
+		.await
+		.assert_status(200)
+		.assert_header("x-uri", "/hello?a=b");
+
+	make_request!("GET", addr, "/tower")
+		.await
+		.assert_status(200)
+		.assert_header("x-uri", "/");
+
+	make_request!("POST", addr, "/tower/echo", "body")
+		.await
+		.assert_status(200)
+		.assert_body_str("body")
+		.await;
+
+	// other routes are not affected
+	make_request!("GET", addr, "/")
+		.await
+		.assert_status(200)
+		.assert_body_str("Hello, World!")
+		.await;
+}
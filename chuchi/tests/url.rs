@@ -0,0 +1,97 @@
+use chuchi::extractor::PathStr;
+use chuchi::routes::{fill_path, Scope, UrlError};
+use chuchi::{get, Chuchi};
+
+#[macro_use]
+mod util;
+
+#[get("/users/{id}/files/{*path}", name = "user_file")]
+fn user_file(id: &PathStr, path: &PathStr) -> String {
+	format!("{id}:{path}")
+}
+
+#[get("/search/{*?rest}")]
+fn search(rest: &PathStr) -> String {
+	rest.to_string()
+}
+
+#[test]
+fn fill_path_encodes() {
+	let path = fill_path("/a/{b}/{{c}}", &[("b", "x/y z?#%")]).unwrap();
+	assert_eq!(path, "/a/x%2Fy%20z%3F%23%25/{c}");
+
+	let path = fill_path("/a/{*rest}", &[("rest", "x/y z")]).unwrap();
+	assert_eq!(path, "/a/x/y%20z");
+
+	let path = fill_path("/a/{*?rest}", &[]).unwrap();
+	assert_eq!(path, "/a/");
+
+	let err = fill_path("/a/{b}", &[]).unwrap_err();
+	assert!(matches!(err, UrlError::MissingParam(p) if p == "b"));
+
+	let err = fill_path("/a/{b", &[("b", "c")]).unwrap_err();
+	assert!(matches!(err, UrlError::UnclosedParam(t) if t == "/a/{b"));
+}
+
+#[test]
+fn fill_path_dot_segments() {
+	let path = fill_path("/a/{b}/{c}", &[("b", ".."), ("c", ".")]).unwrap();
+	assert_eq!(path, "/a/%2E%2E/%2E");
+
+	let path = fill_path("/a/{*rest}", &[("rest", "../b/./c.d/..")]).unwrap();
+	assert_eq!(path, "/a/%2E%2E/b/%2E/c.d/%2E%2E");
+}
+
+#[test]
+fn route_url() {
+	assert_eq!(user_file.url(1, "a b/c.txt"), "/users/1/files/a%20b/c.txt");
+	assert_eq!(search.url(""), "/search/");
+}
+
+#[test]
+fn url_for() {
+	let mut server = Chuchi::new_localhost();
+	server.add_route(search);
+
+	let mut scope = Scope::new();
+	scope.add_route(user_file);
+	server.mount("/api", scope);
+
+	let url = server.url_for("user_file", &[("id", "1"), ("path", "a/b")]);
+	assert_eq!(url.unwrap(), "/api/users/1/files/a/b");
+
+	let err = server.url_for("search", &[]).unwrap_err();
+	assert!(matches!(err, UrlError::UnknownRoute(n) if n == "search"));
+
+	let shared = server.into_shared();
+	let url = shared.url_for("user_file", &[("id", "é"), ("path", "x")]);
+	assert_eq!(url.unwrap(), "/api/users/%C3%A9/files/x");
+}
+
+#[test]
+#[should_panic(expected = "already used")]
+fn duplicate_name() {
+	#[get("/other", name = "user_file")]
+	fn other() -> &'static str {
+		""
+	}
+
+	let mut server = Chuchi::new_localhost();
+	server.add_route(user_file);
+	server.add_route(other);
+}
+
+#[tokio::test]
+async fn url_roundtrip() {
+	let addr = spawn_server!(|builder| {
+		builder.add_route(user_file);
+	});
+
+	let url = user_file.url("a b", "x/y?z.txt");
+	make_request!("GET", addr, url)
+		.await
+		.assert_status(200)
+		// path params are not decoded
+		.assert_body_str("a%20b:x/y%3Fz.txt")
+		.await;
+}
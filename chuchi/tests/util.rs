@@ -125,6 +125,19 @@ impl TestResponse {
 		self.inner.headers().get(key).and_then(|v| v.to_str().ok())
 	}
 
+	pub fn headers(&self, key: &str) -> Vec<&str> {
+		self.inner
+			.headers()
+			.get_all(key)
+			.iter()
+			.filter_map(|v| v.to_str().ok())
+			.collect()
+	}
+
+	pub fn take_body(&mut self) -> Body {
+		self.inner.body_mut().take()
+	}
+
 	pub async fn assert_body_str(mut self, value: &str) -> Self {
 		let body = self
 			.inner